@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::runner::Part;
+
+pub const DEFAULT_PATH: &str = "answers.toml";
+
+/// The expected answers, one table per day:
+///
+/// ```toml
+/// [20]
+/// part1 = 5765
+/// part2 = 18509
+/// ```
+#[derive(Debug, Default)]
+pub struct Answers(HashMap<(u8, Part), String>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail { expected: String },
+    Missing,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Pass => write!(f, "PASS"),
+            Status::Fail { expected } => write!(f, "FAIL (expected {})", expected),
+            Status::Missing => write!(f, "MISSING"),
+        }
+    }
+}
+
+impl Answers {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = read_to_string(path).map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+        Self::parse(&content).map_err(|err| format!("Invalid answers in {}: {}", path.display(), err))
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let table: toml::Table = content.parse().map_err(|err: toml::de::Error| err.message().to_string())?;
+
+        let mut answers = HashMap::new();
+        for (day, parts) in table {
+            let day_number = day.parse().map_err(|_| format!("'{}' is not a day number", day))?;
+            let parts = parts.as_table().ok_or_else(|| format!("[{}] should be a table", day))?;
+            for (key, value) in parts {
+                let part = match key.as_str() {
+                    "part1" => Part::One,
+                    "part2" => Part::Two,
+                    _ => return Err(format!("unknown key '{}' in [{}]", key, day)),
+                };
+                let answer = match value {
+                    toml::Value::String(answer) => answer.clone(),
+                    toml::Value::Integer(answer) => answer.to_string(),
+                    _ => return Err(format!("{}.{} should be a string or an integer", day, key)),
+                };
+                answers.insert((day_number, part), answer);
+            }
+        }
+
+        Ok(Self(answers))
+    }
+
+    pub fn check(&self, day: u8, part: Part, answer: &str) -> Status {
+        match self.0.get(&(day, part)) {
+            None => Status::Missing,
+            Some(expected) if expected == answer => Status::Pass,
+            Some(expected) => Status::Fail {
+                expected: expected.clone(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let answers = Answers::parse("[20]\npart1 = 5765\n\n[25]\npart2 = \"MERRY CHRISTMAS\"\n").unwrap();
+        assert_eq!(answers.check(20, Part::One, "5765"), Status::Pass);
+        assert_eq!(
+            answers.check(20, Part::One, "5766"),
+            Status::Fail {
+                expected: "5765".to_string()
+            }
+        );
+        assert_eq!(answers.check(20, Part::Two, "18509"), Status::Missing);
+        assert_eq!(answers.check(25, Part::Two, "MERRY CHRISTMAS"), Status::Pass);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Answers::parse("[day20]\npart1 = 1").is_err());
+        assert!(Answers::parse("[20]\npart3 = 1").is_err());
+        assert!(Answers::parse("[20]\npart1 = 1.5").is_err());
+        assert!(Answers::parse("[20\n").is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs::{read_to_string, write};
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::runner::Part;
+use crate::{Loader, Result};
+
+pub const DEFAULT_THRESHOLD: f64 = 10.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        assert!(!samples.is_empty(), "Cannot compute statistics without samples");
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        };
+
+        let secs = sorted.iter().map(|sample| sample.as_secs_f64()).collect::<Vec<_>>();
+        let mean = secs.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            secs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.
+        };
+
+        Self {
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min {:?}, median {:?}, mean {:?}, stddev {:?}",
+            self.min, self.median, self.mean, self.stddev
+        )
+    }
+}
+
+/// What is being measured: parsing the input, or solving one of the parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Step {
+    Parse,
+    Solve(Part),
+}
+
+impl Step {
+    fn key(&self) -> &'static str {
+        match self {
+            Step::Parse => "parse",
+            Step::Solve(Part::One) => "part1",
+            Step::Solve(Part::Two) => "part2",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "parse" => Some(Step::Parse),
+            "part1" => Some(Step::Solve(Part::One)),
+            "part2" => Some(Step::Solve(Part::Two)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Parse => write!(f, "Parsing"),
+            Step::Solve(part) => write!(f, "Part {}", part),
+        }
+    }
+}
+
+/// Runs the parser and the requested parts `runs` times each.
+/// Every part is solved on a freshly parsed state, so that no run benefits from the work of a previous one
+pub fn bench(loader: Loader, input: &str, parts: &[Part], runs: usize) -> Result<Vec<(Step, Stats)>> {
+    let mut parse_samples = vec![];
+    let mut part_samples = vec![vec![]; parts.len()];
+
+    for _ in 0..runs {
+        let start = Instant::now();
+        let mut solution = Some(loader(input)?);
+        parse_samples.push(start.elapsed());
+
+        for (part, samples) in parts.iter().zip(part_samples.iter_mut()) {
+            let mut solution = match solution.take() {
+                Some(solution) => solution,
+                None => loader(input)?,
+            };
+
+            let start = Instant::now();
+            black_box(part.solve(solution.as_mut()));
+            samples.push(start.elapsed());
+        }
+    }
+
+    let mut stats = vec![(Step::Parse, Stats::from_samples(&parse_samples))];
+    for (part, samples) in parts.iter().zip(part_samples) {
+        stats.push((Step::Solve(*part), Stats::from_samples(&samples)));
+    }
+
+    Ok(stats)
+}
+
+/// Benchmark results of several days, which can be saved and compared with a later run
+#[derive(Debug, Default)]
+pub struct BenchHistory(BTreeMap<(u8, Step), Stats>);
+
+impl BenchHistory {
+    pub fn load(path: impl AsRef<Path>) -> std::result::Result<Self, String> {
+        let path = path.as_ref();
+        let content = read_to_string(path).map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+        Self::parse(&content).map_err(|err| format!("Invalid benchmark results in {}: {}", path.display(), err))
+    }
+
+    /// Loads the existing results to update, if any
+    pub fn load_or_default(path: impl AsRef<Path>) -> std::result::Result<Self, String> {
+        match path.as_ref().exists() {
+            true => Self::load(path),
+            false => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::result::Result<(), String> {
+        let path = path.as_ref();
+        write(path, self.to_toml()).map_err(|err| format!("Could not write {}: {}", path.display(), err))
+    }
+
+    pub fn insert(&mut self, day: u8, step: Step, stats: Stats) {
+        self.0.insert((day, step), stats);
+    }
+
+    pub fn get(&self, day: u8, step: Step) -> Option<&Stats> {
+        self.0.get(&(day, step))
+    }
+
+    fn parse(content: &str) -> std::result::Result<Self, String> {
+        let table: toml::Table = content.parse().map_err(|err: toml::de::Error| err.message().to_string())?;
+
+        let mut history = Self::default();
+        for (day, steps) in table {
+            let day_number = day.parse().map_err(|_| format!("'{}' is not a day number", day))?;
+            let steps = steps.as_table().ok_or_else(|| format!("[{}] should be a table", day))?;
+            for (key, stats) in steps {
+                let step = Step::from_key(key).ok_or_else(|| format!("unknown step '{}' in [{}]", key, day))?;
+                let duration = |name: &str| {
+                    stats
+                        .get(name)
+                        .and_then(|value| value.as_integer())
+                        .map(|nanos| Duration::from_nanos(nanos as u64))
+                        .ok_or_else(|| format!("missing {} in [{}.{}]", name, day, key))
+                };
+                history.insert(
+                    day_number,
+                    step,
+                    Stats {
+                        min: duration("min_ns")?,
+                        median: duration("median_ns")?,
+                        mean: duration("mean_ns")?,
+                        stddev: duration("stddev_ns")?,
+                    },
+                );
+            }
+        }
+
+        Ok(history)
+    }
+
+    fn to_toml(&self) -> String {
+        let mut table = toml::Table::new();
+        for ((day, step), stats) in &self.0 {
+            let mut values = toml::Table::new();
+            for (name, duration) in [
+                ("min_ns", stats.min),
+                ("median_ns", stats.median),
+                ("mean_ns", stats.mean),
+                ("stddev_ns", stats.stddev),
+            ] {
+                values.insert(name.to_string(), toml::Value::Integer(duration.as_nanos() as i64));
+            }
+
+            table
+                .entry(day.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .unwrap()
+                .insert(step.key().to_string(), toml::Value::Table(values));
+        }
+
+        table.to_string()
+    }
+}
+
+/// Relative change of the median, in percent, when it exceeds the threshold
+pub fn regression(current: &Stats, baseline: &Stats, threshold: f64) -> Option<f64> {
+    let baseline = baseline.median.as_secs_f64();
+    if baseline == 0. {
+        return None;
+    }
+
+    let change = (current.median.as_secs_f64() - baseline) / baseline * 100.;
+    (change > threshold).then_some(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|value| Duration::from_millis(*value)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(&ms(&[4, 1, 3, 2]));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_micros(2500));
+        assert_eq!(stats.mean, Duration::from_micros(2500));
+        assert_eq!(stats.stddev.as_micros(), 1290);
+
+        let stats = Stats::from_samples(&ms(&[5]));
+        assert_eq!(stats.median, Duration::from_millis(5));
+        assert_eq!(stats.stddev, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let mut history = BenchHistory::default();
+        let stats = Stats::from_samples(&ms(&[1, 2, 6]));
+        history.insert(20, Step::Parse, stats);
+        history.insert(20, Step::Solve(Part::Two), stats);
+
+        let loaded = BenchHistory::parse(&history.to_toml()).unwrap();
+        assert_eq!(loaded.get(20, Step::Parse), Some(&stats));
+        assert_eq!(loaded.get(20, Step::Solve(Part::Two)), Some(&stats));
+        assert_eq!(loaded.get(20, Step::Solve(Part::One)), None);
+    }
+
+    #[test]
+    fn test_regression() {
+        let baseline = Stats::from_samples(&ms(&[10]));
+        assert_eq!(regression(&Stats::from_samples(&ms(&[11])), &baseline, 10.), None);
+        assert_eq!(regression(&Stats::from_samples(&ms(&[8])), &baseline, 10.), None);
+        assert!(regression(&Stats::from_samples(&ms(&[12])), &baseline, 10.).is_some());
+    }
+}
@@ -1,21 +1,135 @@
-#![allow(dead_code)]
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::process::exit;
+use std::sync::{mpsc, Arc};
+use std::time::Instant;
 
-use advent::day18;
-use advent::day19;
-use advent::day20;
-use advent::day21;
-use advent::day22;
-use advent::day23;
-use advent::day24;
-use advent::day25;
+use threadpool::ThreadPool;
+
+use advent::bench::BenchHistory;
+use advent::report::{self, Format};
+use advent::runner::{self, Input, Options, USAGE_ALL};
+use advent::REGISTRY;
 
 fn main() {
-    day18::Solution::init().solve();
-    day19::Solution::init().solve();
-    day20::Solution::init().solve();
-    day21::Solution::init().solve();
-    day22::Solution::init().solve();
-    day23::Solution::init().solve();
-    day24::Solution::init().solve();
-    day25::Solution::init().solve();
+    let options = match Options::from_args(std::env::args().skip(1)) {
+        Ok(options) if options.day.is_none() && options.input == Input::Default && !options.trace => options,
+        Ok(options) if options.trace => {
+            eprintln!("--trace only applies to a single day\n{}", USAGE_ALL);
+            exit(2);
+        }
+        Ok(_) => {
+            eprintln!("all runs every day on its default input\n{}", USAGE_ALL);
+            exit(2);
+        }
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE_ALL);
+            exit(2);
+        }
+    };
+
+    if options.bench.is_some() {
+        if let Err(err) = bench(&options) {
+            eprintln!("{}", err);
+            exit(1);
+        }
+        return;
+    }
+
+    let answers = match options.load_answers() {
+        Ok(answers) => Arc::new(answers),
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    };
+
+    let start = Instant::now();
+    let pool = ThreadPool::new(options.workers());
+    let (sender, receiver) = mpsc::channel();
+    for (day, loader) in REGISTRY {
+        let sender = sender.clone();
+        let parts = options.parts.clone();
+        let answers = Arc::clone(&answers);
+        let timeout = options.timeout;
+        pool.execute(move || {
+            let mut output = vec![];
+            let report = runner::run_day(
+                &mut output,
+                *day,
+                *loader,
+                &Input::Default,
+                &parts,
+                answers.as_ref().as_ref(),
+                timeout,
+            )
+            .expect("Could not write to a buffer");
+            sender.send((report, output)).unwrap();
+        });
+    }
+    drop(sender);
+
+    // Print the days in order as soon as all the previous ones are done
+    let mut pending = BTreeMap::new();
+    let mut next_days = REGISTRY.iter().map(|(day, _)| *day).peekable();
+    let mut reports = vec![];
+    for (report, output) in receiver {
+        pending.insert(report.day, (report, output));
+        while let Some((report, output)) = next_days.peek().and_then(|day| pending.remove(day)) {
+            if options.format == Format::Text {
+                io::stdout().write_all(&output).unwrap();
+                if let Some(err) = &report.error {
+                    eprintln!("{}\n", err);
+                }
+            }
+            reports.push(report);
+            next_days.next();
+        }
+    }
+
+    match options.format {
+        Format::Text => print!("{}", runner::summary(&reports, start.elapsed())),
+        format => {
+            let records = report::records(&reports, &options.parts);
+            report::write(&mut io::stdout(), format, &records).expect("Could not write to stdout");
+        }
+    }
+
+    if reports.iter().any(|report| report.failed()) {
+        exit(1);
+    }
+}
+
+/// Benchmarks run one day after the other, so that they don't compete for the cores
+fn bench(options: &Options) -> Result<(), String> {
+    let baseline = options.load_baseline()?;
+
+    // Results are merged into the existing file, so that benchmarking a single part keeps the others
+    let mut history = match &options.save {
+        Some(path) => BenchHistory::load_or_default(path)?,
+        None => BenchHistory::default(),
+    };
+
+    for (day, loader) in REGISTRY {
+        let input = match Input::Default.read(*day) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("Could not read the input of day {}: {}\n", day, err);
+                continue;
+            }
+        };
+
+        let stats = runner::bench(&mut io::stdout(), *day, *loader, &input, options, baseline.as_ref())
+            .expect("Could not write to stdout");
+        match stats {
+            Ok(stats) => stats.into_iter().for_each(|(step, stats)| history.insert(*day, step, stats)),
+            Err(err) => eprintln!("Invalid input: {}\n", err),
+        }
+    }
+
+    if let Some(path) = &options.save {
+        history.save(path)?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,32 @@
+use std::path::Path;
+use std::process::exit;
+
+use advent::scaffold;
+
+const USAGE: &str = "Usage: new-day <day>";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let day = match args.as_slice() {
+        [day] => match day.parse() {
+            Ok(day) => day,
+            Err(_) => {
+                eprintln!("Invalid day: {}\n{}", day, USAGE);
+                exit(2);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+
+    // Like the inputs, paths are relative to the root of the crate
+    match scaffold::new_day(Path::new("."), day) {
+        Ok(steps) => steps.iter().for_each(|step| println!("{}", step)),
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    }
+}
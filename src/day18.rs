@@ -1,65 +1,252 @@
 // Inspired by https://www.ericburden.work/blog/2021/12/29/advent-of-code-2021-day-19/
 
-use std::fs::read_to_string;
+use std::cmp::Reverse;
+use std::fmt::Display;
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::thread;
 
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::{Answer, Day, ParseError, Result};
+
+pub mod explore;
+pub mod snailfish;
+
+pub use snailfish::{Node, ReductionRules, SnailfishNumber};
+
 pub struct Solution {
-    lines: Vec<String>,
+    numbers: Vec<SnailfishNumber>,
+    rules: ReductionRules,
+    largest: Option<Option<LargestSum>>,
 }
 
-impl Solution {
-    pub fn init() -> Self {
-        let mut lines = Vec::new();
-        for line in read_to_string("inputs/day18.txt").unwrap().lines() {
-            lines.push(line.to_string());
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
+        let rules = ReductionRules::default();
+        let numbers: Vec<_> = input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| snailfish::validate(line, &rules).map_err(|err| err.on_line(i + 1)))
+            .collect::<Result<_>>()?;
+        if numbers.is_empty() {
+            return Err(ParseError::new(DAY, 1, 1, "empty input"));
         }
 
-        Self { lines }
+        Ok(Self {
+            numbers,
+            rules,
+            largest: None,
+        })
+    }
+
+    fn part1(&mut self) -> Answer {
+        let mut numbers = self.numbers.iter();
+        let first = numbers.next().unwrap().clone();
+        let sum = numbers.fold(first, |acc, number| acc.add_with(number, &self.rules));
+        Box::new(sum.magnitude_with(&self.rules))
     }
 
-    fn part1(&mut self) -> T {
-        let mut numbers = self.lines.iter().map(|line| parse(line));
-        let first = numbers.next().unwrap();
-        let addition_result = numbers.fold(first, |acc, num| add(acc, num));
-        magnitude(&addition_result)
+    fn part2(&mut self) -> Answer {
+        let largest = self.largest_pair().expect("Need at least two numbers");
+        Box::new(largest.magnitude)
     }
 
-    fn part2(&mut self) -> T {
-        let pairs = self.lines.iter().map(|line| parse(line)).combinations(2);
-        let mut max_mag = 0;
-        for pair in pairs {
-            let mag_addition_forward = magnitude(&add(pair[0].clone(), pair[1].clone()));
-            let mag_addition_backward = magnitude(&add(pair[1].clone(), pair[0].clone()));
-            max_mag = max_mag.max(mag_addition_forward).max(mag_addition_backward);
+    /// Every addition of the homework, with the actions that reduce it, then the ordered pair behind part 2
+    fn trace(&mut self, out: &mut dyn Write) -> io::Result<bool> {
+        let mut numbers = self.numbers.iter();
+        let Some(mut sum) = numbers.next().cloned() else {
+            return Ok(true);
+        };
+
+        for number in numbers {
+            writeln!(out, "  {}\n+ {}", sum, number)?;
+            let mut reduction = sum.add_traced(number, &self.rules);
+            writeln!(out, "{:<16}{}", "after addition:", reduction.number())?;
+            for step in reduction.by_ref() {
+                writeln!(out, "{}", step)?;
+            }
+            sum = reduction.finish();
+            writeln!(out, "= {}\n", sum)?;
+        }
+        writeln!(out, "magnitude: {}", sum.magnitude_with(&self.rules))?;
+
+        if let Some(largest) = self.largest_pair() {
+            writeln!(
+                out,
+                "largest magnitude of two different numbers: {} (line {} + line {})",
+                largest.magnitude,
+                largest.left + 1,
+                largest.right + 1
+            )?;
         }
 
-        max_mag
+        Ok(true)
     }
+}
 
-    pub fn solve(&mut self) {
-        println!("========= DAY 18 ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
+impl Solution {
+    /// The pair of part 2 with its line indices, searched once
+    pub fn largest_pair(&mut self) -> Option<LargestSum> {
+        *self
+            .largest
+            .get_or_insert_with(|| largest_sum(&self.numbers, &self.rules, workers()))
+    }
+}
 
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
+/// The ordered pair of different numbers whose sum has the largest magnitude, as indices in the homework
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LargestSum {
+    pub left: usize,
+    pub right: usize,
+    pub magnitude: T,
+}
+
+fn workers() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
 
-        print!("Solving part 2: ");
-        io::stdout().flush().unwrap();
-        let start = std::time::Instant::now();
-        let part2 = self.part2();
-        let part2_time = start.elapsed();
-        println!("{:?} (took {:?})", part2, part2_time);
-        println!();
+/// Tries the ordered pairs of different numbers, the left operands being spread over `workers` scoped threads
+/// which all borrow the same numbers. The left operands are tried from the most promising one, and those which
+/// can't beat the best magnitude found so far by any thread are skipped. Ties go to the first pair in reading order
+pub fn largest_sum(numbers: &[SnailfishNumber], rules: &ReductionRules, workers: usize) -> Option<LargestSum> {
+    let workers = workers.clamp(1, numbers.len().max(1));
+    // Larger magnitudes first, then earlier pairs
+    let key = |sum: &LargestSum| (sum.magnitude, Reverse((sum.left, sum.right)));
+
+    let bounds = &Bounds::new(numbers, rules);
+    let left_bounds = (0..numbers.len()).map(|left| bounds.left(left)).collect_vec();
+    let order = &(0..numbers.len()).sorted_by_key(|left| Reverse(left_bounds[*left])).collect_vec();
+    let left_bounds = &left_bounds;
+    // Only ever a magnitude that was reached, so skipping the pairs bounded below it is safe.
+    // A pair bounded exactly at it is still tried, as it could win a tie
+    let best = &AtomicI32::new(T::MIN);
+
+    thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|worker| {
+                scope.spawn(move || {
+                    order
+                        .iter()
+                        .skip(worker)
+                        .step_by(workers)
+                        .copied()
+                        .filter(|left| left_bounds[*left] >= best.load(Ordering::Relaxed))
+                        .flat_map(|left| {
+                            (0..numbers.len())
+                                .filter(move |right| *right != left)
+                                .filter(move |right| bounds.pair(left, *right) >= best.load(Ordering::Relaxed))
+                                .map(move |right| (left, right))
+                        })
+                        .map(|(left, right)| {
+                            let magnitude = numbers[left].add_with(&numbers[right], rules).magnitude_with(rules);
+                            best.fetch_max(magnitude, Ordering::Relaxed);
+                            LargestSum { left, right, magnitude }
+                        })
+                        .max_by_key(key)
+                })
+            })
+            .collect_vec();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .max_by_key(key)
+    })
+}
+
+/// Upper bounds of the magnitudes of sums, from the totals of the regular numbers of the operands.
+/// Explosions and splits never increase that total, as exploded values are only lost off the edges, and the regular
+/// numbers of a reduced sum are below the split threshold. At best, the total fills the regular numbers weighing the
+/// most in a full tree of the explode depth, which no shallower regular number can outweigh
+struct Bounds {
+    /// None when the rules or the numbers allow no bound, with negative weights or regular numbers
+    totals: Option<Vec<T>>,
+    /// The weight in the magnitude of each position of the full tree, as (weight, number of positions), heaviest first
+    weights: Vec<(i64, i64)>,
+    largest_regular: i64,
+}
+
+impl Bounds {
+    fn new(numbers: &[SnailfishNumber], rules: &ReductionRules) -> Self {
+        let (left_weight, right_weight) = rules.magnitude_weights;
+        let totals = numbers
+            .iter()
+            .map(regular_total)
+            .collect::<Option<Vec<_>>>()
+            .filter(|_| left_weight >= 0 && right_weight >= 0 && rules.split_threshold > 0);
+
+        let depth = rules.explode_depth as u32;
+        let mut positions: i64 = 1;
+        let mut weights = (0..=depth)
+            .map(|lefts| {
+                let weight = (left_weight as i64)
+                    .saturating_pow(lefts)
+                    .saturating_mul((right_weight as i64).saturating_pow(depth - lefts));
+                let count = positions;
+                // The number of positions with one more left turn, C(depth, lefts + 1)
+                positions = positions.saturating_mul((depth - lefts) as i64) / (lefts as i64 + 1);
+                (weight, count)
+            })
+            .collect_vec();
+        weights.sort_by_key(|(weight, _)| Reverse(*weight));
+
+        Self {
+            totals,
+            weights,
+            largest_regular: rules.split_threshold as i64 - 1,
+        }
+    }
+
+    /// The largest magnitude of a reduced number whose regular numbers add up to `total`
+    fn of_total(&self, total: T) -> T {
+        let mut remaining = total as i64;
+        let mut magnitude: i64 = 0;
+        for (weight, count) in &self.weights {
+            let used = remaining.min(count.saturating_mul(self.largest_regular));
+            magnitude = magnitude.saturating_add(weight.saturating_mul(used));
+            remaining -= used;
+        }
+        magnitude.min(T::MAX as i64) as T
+    }
+
+    fn pair(&self, left: usize, right: usize) -> T {
+        match &self.totals {
+            Some(totals) => self.of_total(totals[left].saturating_add(totals[right])),
+            None => T::MAX,
+        }
+    }
+
+    /// The bound of the sums of `left` with any other number
+    fn left(&self, left: usize) -> T {
+        match &self.totals {
+            Some(totals) => (0..totals.len())
+                .filter(|right| *right != left)
+                .map(|right| self.pair(left, right))
+                .max()
+                .unwrap_or(T::MIN),
+            None => T::MAX,
+        }
     }
 }
 
+/// The total of the regular numbers, when none is negative
+fn regular_total(number: &SnailfishNumber) -> Option<T> {
+    let mut total: T = 0;
+    let mut pending = vec![number.root()];
+    while let Some(id) = pending.pop() {
+        match number.node(id) {
+            Node::Regular(value) if value < 0 => return None,
+            Node::Regular(value) => total = total.saturating_add(value),
+            Node::Pair(left, right) => pending.extend([left, right]),
+        }
+    }
+    Some(total)
+}
+
 type T = i32;
+/// The flat token representation the solution started with, kept as a reference for the tree
 type Number = Vec<Symbol>;
 type Depth = usize;
 
@@ -71,35 +258,65 @@ enum Symbol {
     Comma,
 }
 
-fn parse(input: &str) -> Number {
-    let mut symbols = Vec::new();
+const DAY: u8 = 18;
 
-    for token in input.chars() {
-        match token {
-            '0'..='9' => symbols.push(Symbol::Number(token.to_digit(10).unwrap() as T)),
-            '[' => symbols.push(Symbol::LBracket),
-            ']' => symbols.push(Symbol::RBracket),
-            ',' => symbols.push(Symbol::Comma),
-            _ => (),
+impl Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Symbol::Number(n) => write!(f, "{}", n),
+            Symbol::LBracket => write!(f, "["),
+            Symbol::RBracket => write!(f, "]"),
+            Symbol::Comma => write!(f, ","),
         }
     }
-
-    symbols
 }
 
-fn format(input: &Number) -> String {
-    let mut output = String::new();
-
-    for symbol in input {
-        match symbol {
-            Symbol::Number(n) => output.push_str(&n.to_string()),
-            Symbol::LBracket => output.push('['),
-            Symbol::RBracket => output.push(']'),
-            Symbol::Comma => output.push(','),
-        }
+/// Splits a line into symbols, each with the column it starts at.
+/// Regular numbers can have several digits and a leading minus sign
+fn tokenize(input: &str) -> Result<Vec<(usize, Symbol)>> {
+    let chars = input.chars().collect_vec();
+    let mut tokens = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let symbol = match chars[i] {
+            '[' => Symbol::LBracket,
+            ']' => Symbol::RBracket,
+            ',' => Symbol::Comma,
+            '-' | '0'..='9' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let literal = chars[start..i].iter().collect::<String>();
+                if literal == "-" {
+                    return Err(ParseError::new(DAY, 1, column, "expected digits after '-'"));
+                }
+                let value = literal
+                    .parse()
+                    .map_err(|_| ParseError::new(DAY, 1, column, format!("number '{}' is too large", literal)))?;
+                tokens.push((column, Symbol::Number(value)));
+                continue;
+            }
+            c => return Err(ParseError::new(DAY, 1, column, format!("unexpected character '{}'", c))),
+        };
+        tokens.push((column, symbol));
+        i += 1;
     }
 
-    output
+    Ok(tokens)
+}
+
+fn parse(input: &str) -> Result<Number> {
+    // The tree parser checks the structure, so that the functions below only see well-formed numbers
+    input.parse::<SnailfishNumber>()?;
+    Ok(tokenize(input)?.into_iter().map(|(_, symbol)| symbol).collect())
+}
+
+fn format(input: &Number) -> String {
+    input.iter().map(|symbol| symbol.to_string()).collect()
 }
 
 fn find_first_regular_left(value: &Number, left_index: usize) -> Option<usize> {
@@ -158,7 +375,7 @@ fn explode_at(number: &mut Number, left_index: usize) {
     number.splice(left_index - 1..=right_index + 1, vec![Symbol::Number(0)]);
 }
 
-fn explode_once(value: &mut Number) -> bool {
+fn explode_once(value: &mut Number, rules: &ReductionRules) -> bool {
     // Find the left most pair, if any, and if it's depth is greater than 4, explode it
     let mut depth = 0;
     for i in 0..value.len()-2 {
@@ -168,7 +385,7 @@ fn explode_once(value: &mut Number) -> bool {
             _ => (),
         }
         match (&value[i], &value[i + 1], &value[i + 2]) {
-            (Symbol::Number(_), Symbol::Comma, Symbol::Number(_)) if depth > 4 => {
+            (Symbol::Number(_), Symbol::Comma, Symbol::Number(_)) if depth > rules.explode_depth => {
                 explode_at(value, i);
                 return true;
             }
@@ -179,12 +396,9 @@ fn explode_once(value: &mut Number) -> bool {
     false
 }
 
-fn split_at(number: &mut Number, regular_number_index: usize) {
+fn split_at(number: &mut Number, regular_number_index: usize, rules: &ReductionRules) {
     let value = get_value(number, regular_number_index);
-    // let left_value = value.div_floor(&2);
-    // let right_value = value.div_ceil(&2);
-    let left_value = value / 2;
-    let right_value = value - left_value;
+    let (left_value, right_value) = rules.split(value);
     number.splice(
         regular_number_index..regular_number_index + 1,
         vec![
@@ -197,11 +411,11 @@ fn split_at(number: &mut Number, regular_number_index: usize) {
     );
 }
 
-fn split_once(number: &mut Number) -> bool {
+fn split_once(number: &mut Number, rules: &ReductionRules) -> bool {
     for i in 0..number.len() {
         match number[i] {
-            Symbol::Number(n) if n >= 10 => {
-                split_at(number, i);
+            Symbol::Number(n) if n >= rules.split_threshold => {
+                split_at(number, i, rules);
                 return true;
             }
             _ => (),
@@ -211,7 +425,7 @@ fn split_once(number: &mut Number) -> bool {
     false
 }
 
-fn add(num_a: Number, num_b: Number) -> Number {
+fn add(num_a: Number, num_b: Number, rules: &ReductionRules) -> Number {
     let mut result = vec![Symbol::LBracket]
         .into_iter()
         .chain(num_a.into_iter())
@@ -222,11 +436,11 @@ fn add(num_a: Number, num_b: Number) -> Number {
 
     // Apply reduction
     loop {
-        if explode_once(&mut result) {
+        if explode_once(&mut result, rules) {
             continue;
         }
 
-        let split = split_once(&mut result);
+        let split = split_once(&mut result, rules);
         if !split {
             break;
         }
@@ -234,7 +448,7 @@ fn add(num_a: Number, num_b: Number) -> Number {
     result
 }
 
-fn magnitude(number: &Number) -> T {
+fn magnitude(number: &Number, rules: &ReductionRules) -> T {
     let mut expressions_queue = vec![];
     for token in number {
         match token {
@@ -246,7 +460,7 @@ fn magnitude(number: &Number) -> T {
                 }
                 let right = expressions_queue.pop().unwrap();
                 let left = expressions_queue.pop().unwrap();
-                expressions_queue.push(3 * left + 2 * right);
+                expressions_queue.push(rules.magnitude(left, right));
             }
         }
     }
@@ -260,13 +474,16 @@ fn magnitude(number: &Number) -> T {
 
 #[cfg(test)]
 mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
     use super::*;
 
     #[test]
     fn parse1() {
         let input = "2";
         let expected = vec![Symbol::Number(2)];
-        assert_eq!(parse(input), expected);
+        assert_eq!(parse(input).unwrap(), expected);
     }
 
     #[test]
@@ -279,7 +496,7 @@ mod tests {
             Symbol::Number(2),
             Symbol::RBracket,
         ];
-        assert_eq!(parse(input), expected);
+        assert_eq!(parse(input).unwrap(), expected);
     }
 
     #[test]
@@ -296,7 +513,49 @@ mod tests {
             Symbol::RBracket,
             Symbol::RBracket,
         ];
-        assert_eq!(parse(input), expected);
+        assert_eq!(parse(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let err = parse("[1,x]").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 4));
+
+        let err = Solution::parse("[1,2]\n[3,4]\n[5;6]").err().unwrap();
+        assert_eq!((err.day, err.line, err.column), (18, 3, 3));
+
+        let err = parse("[1,[2,3],4]").unwrap_err();
+        assert_eq!(err.column, 9);
+
+        let err = Solution::parse("[1,2]\n[[3,4],[5,[6,[7,[8,9]]]]]").err().unwrap();
+        assert_eq!((err.line, err.column), (2, 17));
+
+        let err = Solution::parse("").err().unwrap();
+        assert_eq!((err.line, err.column, err.message.as_str()), (1, 1, "empty input"));
+    }
+
+    #[test]
+    fn parse_multi_digit() {
+        let expected = vec![
+            Symbol::LBracket,
+            Symbol::Number(12),
+            Symbol::Comma,
+            Symbol::Number(-3),
+            Symbol::RBracket,
+        ];
+        assert_eq!(parse("[12,-3]").unwrap(), expected);
+
+        let err = parse("[1,-]").unwrap_err();
+        assert_eq!(err.column, 4);
+        let err = parse("[1,99999999999]").unwrap_err();
+        assert_eq!(err.column, 4);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for input in ["[[[[0,7],4],[15,[0,13]]],[1,1]]", "[-4,[10,-12]]", "123"] {
+            assert_eq!(format(&parse(input).unwrap()), input);
+        }
     }
 
     #[test]
@@ -318,7 +577,7 @@ mod tests {
 
     #[test]
     fn test_explode_at() {
-        let mut input = parse("[[[[[9,8],1],2],3],4]");
+        let mut input = parse("[[[[[9,8],1],2],3],4]").unwrap();
         explode_at(&mut input, 5);
         let expected = "[[[[0,9],2],3],4]";
         assert_eq!(format(&input), expected);
@@ -326,17 +585,17 @@ mod tests {
 
     #[test]
     fn test_explode_once() {
-        let mut input = parse("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]");
+        let mut input = parse("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]").unwrap();
         let expected = "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]";
-        explode_once(&mut input);
+        explode_once(&mut input, &ReductionRules::default());
         assert_eq!(format(&input), expected);
     }
 
     #[test]
     fn test_explode_once2() {
-        let mut input = parse("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]");
+        let mut input = parse("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]").unwrap();
         let expected = "[[3,[2,[8,0]]],[9,[5,[7,0]]]]";
-        explode_once(&mut input);
+        explode_once(&mut input, &ReductionRules::default());
         assert_eq!(format(&input), expected);
     }
 
@@ -344,7 +603,7 @@ mod tests {
     fn test_split_at() {
         let mut input = vec![Symbol::Number(10)];
         let expected = "[5,5]";
-        split_at(&mut input, 0);
+        split_at(&mut input, 0, &ReductionRules::default());
         assert_eq!(format(&input), expected);
     }
 
@@ -352,16 +611,82 @@ mod tests {
     fn test_split_at2() {
         let mut input = vec![Symbol::Number(11)];
         let expected = "[5,6]";
-        split_at(&mut input, 0);
+        split_at(&mut input, 0, &ReductionRules::default());
         assert_eq!(format(&input), expected);
     }
 
     #[test]
     fn test_add1() {
-        let a = parse("[[[[4,3],4],4],[7,[[8,4],9]]]");
-        let b = parse("[1,1]");
-        let addition = add(a, b);
+        let a = parse("[[[[4,3],4],4],[7,[[8,4],9]]]").unwrap();
+        let b = parse("[1,1]").unwrap();
+        let addition = add(a, b, &ReductionRules::default());
         let expected = "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]";
         assert_eq!(format(&addition), expected);
     }
+
+    #[test]
+    fn test_tree_agrees_with_tokens() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let rules = ReductionRules::default();
+        for _ in 0..2000 {
+            let a = SnailfishNumber::random(&mut rng, &rules);
+            let b = SnailfishNumber::random(&mut rng, &rules);
+            let tokens = add(parse(&a.to_string()).unwrap(), parse(&b.to_string()).unwrap(), &rules);
+
+            let sum = &a + &b;
+            assert_eq!(sum.to_string(), format(&tokens), "{} + {}", a, b);
+            assert_eq!(sum.magnitude(), magnitude(&tokens, &rules), "{} + {}", a, b);
+            assert_eq!(format(&parse(&a.to_string()).unwrap()), a.to_string());
+        }
+    }
+
+    const EXAMPLE: &str = include_str!("../inputs/examples/day18.txt");
+
+    #[test]
+    fn test_largest_sum() {
+        let solution = Solution::parse(EXAMPLE).unwrap();
+        for workers in [1, 3, 16] {
+            let largest = largest_sum(&solution.numbers, &solution.rules, workers).unwrap();
+            assert_eq!(largest, LargestSum { left: 8, right: 0, magnitude: 3993 });
+        }
+        assert_eq!(largest_sum(&solution.numbers[..1], &solution.rules, 4), None);
+    }
+
+    #[test]
+    fn test_magnitude_bounds() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        let bounds = Bounds::new(&solution.numbers, &solution.rules);
+        for (left, right) in (0..solution.numbers.len()).tuple_combinations() {
+            for (left, right) in [(left, right), (right, left)] {
+                let sum = solution.numbers[left].add_with(&solution.numbers[right], &solution.rules);
+                assert!(sum.magnitude_with(&solution.rules) <= bounds.pair(left, right));
+                assert!(bounds.pair(left, right) <= bounds.left(left));
+            }
+        }
+
+        // Filling the heaviest regular numbers of [[[[_,_],[_,_]],...]]] first
+        assert_eq!(bounds.of_total(0), 0);
+        assert_eq!(bounds.of_total(10), 9 * 81 + 54);
+        assert_eq!(bounds.of_total(1000), 9 * 5_i32.pow(4));
+
+        // Negative regular numbers have no bound
+        let negative: SnailfishNumber = "[-1,2]".parse().unwrap();
+        let bounds = Bounds::new(&[negative.clone(), negative], &solution.rules);
+        assert_eq!((bounds.pair(0, 1), bounds.left(0)), (T::MAX, T::MAX));
+
+        let largest = solution.largest_pair().unwrap();
+        assert_eq!((largest.left, largest.right), (8, 0));
+    }
+
+    #[test]
+    fn test_example_part1() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "4140");
+    }
+
+    #[test]
+    fn test_example_part2() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "3993");
+    }
 }
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use super::snailfish::{Direction, Path};
+use super::{format, parse, Number, Symbol, T};
+use crate::Result;
+
+pub const USAGE: &str = "Usage: advent 18 inspect <number> [--dot]";
+
+/// A node of a number, met in reading order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visit {
+    pub path: Path,
+    pub node: Visited,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visited {
+    /// A pair, with its own text
+    Pair(String),
+    Regular(T),
+}
+
+/// The structure of a number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inspection {
+    /// Every node, parents before their children and left before right
+    pub visits: Vec<Visit>,
+}
+
+impl Inspection {
+    /// Walks the symbols of a number, following the brackets and the commas to know where each node sits
+    pub fn new(input: &str) -> Result<Self> {
+        let symbols = parse(input)?;
+        let mut visits = vec![];
+        let mut path = vec![];
+
+        for (i, symbol) in symbols.iter().enumerate() {
+            match symbol {
+                Symbol::LBracket => {
+                    let text = format(&symbols[i..=closing_bracket(&symbols, i)].to_vec());
+                    visits.push(Visit {
+                        path: Path(path.clone()),
+                        node: Visited::Pair(text),
+                    });
+                    path.push(Direction::Left);
+                }
+                Symbol::Number(value) => visits.push(Visit {
+                    path: Path(path.clone()),
+                    node: Visited::Regular(*value),
+                }),
+                Symbol::Comma => {
+                    if let Some(last) = path.last_mut() {
+                        *last = Direction::Right;
+                    }
+                }
+                Symbol::RBracket => {
+                    path.pop();
+                }
+            }
+        }
+
+        Ok(Self { visits })
+    }
+
+    /// The regular numbers with their path, from left to right
+    pub fn leaves(&self) -> impl Iterator<Item = (&Path, T)> {
+        self.visits.iter().filter_map(|visit| match visit.node {
+            Visited::Regular(value) => Some((&visit.path, value)),
+            Visited::Pair(_) => None,
+        })
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves().count()
+    }
+
+    /// The number of pairs around the most nested regular number
+    pub fn max_depth(&self) -> usize {
+        self.leaves().map(|(path, _)| path.0.len()).max().unwrap_or(0)
+    }
+
+    /// One line per node, indented below its parent like the output of `tree`
+    pub fn ascii_tree(&self) -> String {
+        let mut tree = String::new();
+        for visit in &self.visits {
+            if let Some((last, ancestors)) = visit.path.0.split_last() {
+                for direction in ancestors {
+                    tree.push_str(match direction {
+                        Direction::Left => "│   ",
+                        Direction::Right => "    ",
+                    });
+                }
+                tree.push_str(match last {
+                    Direction::Left => "├── ",
+                    Direction::Right => "└── ",
+                });
+            }
+            match &visit.node {
+                Visited::Pair(text) => tree.push_str(text),
+                Visited::Regular(value) => tree.push_str(&value.to_string()),
+            }
+            tree.push('\n');
+        }
+
+        tree
+    }
+
+    /// A Graphviz graph, with the pairs as points and the edges labelled L or R
+    pub fn dot(&self) -> String {
+        let mut ids = HashMap::new();
+        let mut dot = String::from("digraph snailfish {\n");
+        for (id, visit) in self.visits.iter().enumerate() {
+            match &visit.node {
+                Visited::Pair(_) => dot.push_str(&format!("    n{} [label=\"\", shape=point];\n", id)),
+                Visited::Regular(value) => dot.push_str(&format!("    n{} [label=\"{}\", shape=circle];\n", id, value)),
+            }
+            if let Some((last, parent)) = visit.path.0.split_last() {
+                let label = Path(vec![*last]);
+                dot.push_str(&format!("    n{} -> n{} [label=\"{}\"];\n", ids[parent], id, label));
+            }
+            ids.insert(visit.path.0.clone(), id);
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+/// The index of the bracket closing the one at `open`, which `parse` guarantees to exist
+fn closing_bracket(symbols: &Number, open: usize) -> usize {
+    let mut depth = 0;
+    for (i, symbol) in symbols.iter().enumerate().skip(open) {
+        match symbol {
+            Symbol::LBracket => depth += 1,
+            Symbol::RBracket if depth == 1 => return i,
+            Symbol::RBracket => depth -= 1,
+            _ => (),
+        }
+    }
+
+    unreachable!("Unbalanced brackets in a parsed number")
+}
+
+/// `advent 18 inspect <number> [--dot]`: the depth, the leaves and their paths, then the tree
+pub fn run(args: &[String], out: &mut dyn Write) -> std::result::Result<(), String> {
+    let (input, dot) = match args {
+        [input] => (input, false),
+        [input, flag] | [flag, input] if flag == "--dot" => (input, true),
+        _ => return Err(USAGE.to_string()),
+    };
+    let inspection = Inspection::new(input).map_err(|err| format!("Invalid number: {}", err))?;
+
+    let report = if dot {
+        inspection.dot()
+    } else {
+        let mut report = format!(
+            "max depth: {}\nleaves: {}\n",
+            inspection.max_depth(),
+            inspection.leaf_count()
+        );
+        for (path, value) in inspection.leaves() {
+            report.push_str(&format!("  {}: {}\n", path, value));
+        }
+        report.push('\n');
+        report.push_str(&inspection.ascii_tree());
+        report
+    };
+
+    out.write_all(report.as_bytes())
+        .map_err(|err| format!("Could not write the inspection: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let inspection = Inspection::new("[[1,2],3]").unwrap();
+        assert_eq!(inspection.max_depth(), 2);
+        assert_eq!(inspection.leaf_count(), 3);
+        let leaves = inspection
+            .leaves()
+            .map(|(path, value)| format!("{}={}", path, value))
+            .collect::<Vec<_>>();
+        assert_eq!(leaves, ["LL=1", "LR=2", "R=3"]);
+
+        let inspection = Inspection::new("7").unwrap();
+        assert_eq!((inspection.max_depth(), inspection.leaf_count()), (0, 1));
+    }
+
+    #[test]
+    fn test_ascii_tree() {
+        let inspection = Inspection::new("[[1,2],[3,[4,5]]]").unwrap();
+        assert_eq!(
+            inspection.ascii_tree(),
+            "[[1,2],[3,[4,5]]]
+├── [1,2]
+│   ├── 1
+│   └── 2
+└── [3,[4,5]]
+    ├── 3
+    └── [4,5]
+        ├── 4
+        └── 5
+"
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let inspection = Inspection::new("[1,[2,3]]").unwrap();
+        assert_eq!(
+            inspection.dot(),
+            "digraph snailfish {
+    n0 [label=\"\", shape=point];
+    n1 [label=\"1\", shape=circle];
+    n0 -> n1 [label=\"L\"];
+    n2 [label=\"\", shape=point];
+    n0 -> n2 [label=\"R\"];
+    n3 [label=\"2\", shape=circle];
+    n2 -> n3 [label=\"L\"];
+    n4 [label=\"3\", shape=circle];
+    n2 -> n4 [label=\"R\"];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let mut out = vec![];
+        run(&["[[1,2],3]".to_string()], &mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("max depth: 2\nleaves: 3\n  LL: 1\n"));
+
+        assert!(run(&["[[1,2]".to_string()], &mut vec![]).is_err());
+        assert!(run(&[], &mut vec![]).is_err());
+    }
+}
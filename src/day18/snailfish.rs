@@ -0,0 +1,821 @@
+use std::fmt::Display;
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::{ParseError, Result};
+
+use super::{tokenize, Symbol, T, DAY};
+
+/// Which half of an odd regular number goes to the left when it is split
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// The left half is rounded down: 11 becomes [5,6]
+    Down,
+    /// The left half is rounded up: 11 becomes [6,5]
+    Up,
+}
+
+/// The parameters of the reduction and of the magnitude, the ones of the puzzle by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReductionRules {
+    /// A pair of regular numbers is exploded when it is nested inside this many pairs
+    pub explode_depth: usize,
+    /// A regular number is split when it reaches this value
+    pub split_threshold: T,
+    pub split_rounding: Rounding,
+    /// The magnitude of a pair is `left weight * left magnitude + right weight * right magnitude`
+    pub magnitude_weights: (T, T),
+}
+
+impl Default for ReductionRules {
+    fn default() -> Self {
+        Self {
+            explode_depth: 4,
+            split_threshold: 10,
+            split_rounding: Rounding::Down,
+            magnitude_weights: (3, 2),
+        }
+    }
+}
+
+impl ReductionRules {
+    /// The two halves a regular number is split into
+    pub fn split(&self, value: T) -> (T, T) {
+        let (low, high) = (value / 2, value - value / 2);
+        match self.split_rounding {
+            Rounding::Down => (low, high),
+            Rounding::Up => (high, low),
+        }
+    }
+
+    pub fn magnitude(&self, left: T, right: T) -> T {
+        self.magnitude_weights.0 * left + self.magnitude_weights.1 * right
+    }
+}
+
+/// Index of a node in the arena of a `SnailfishNumber`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Node {
+    Regular(T),
+    Pair(NodeId, NodeId),
+}
+
+/// A snailfish number stored as a tree in an arena. Exploding a pair frees the slots of its two
+/// regular numbers, which the next splits reuse, so reducing never reallocates the whole number
+#[derive(Debug, Clone)]
+pub struct SnailfishNumber {
+    nodes: Vec<Node>,
+    root: NodeId,
+    free: Vec<NodeId>,
+}
+
+impl SnailfishNumber {
+    pub fn regular(value: T) -> Self {
+        Self {
+            nodes: vec![Node::Regular(value)],
+            root: NodeId(0),
+            free: vec![],
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn node(&self, id: NodeId) -> Node {
+        self.nodes[id.0]
+    }
+
+    pub fn magnitude(&self) -> T {
+        self.magnitude_with(&ReductionRules::default())
+    }
+
+    pub fn magnitude_with(&self, rules: &ReductionRules) -> T {
+        self.magnitude_of(self.root, rules)
+    }
+
+    fn magnitude_of(&self, id: NodeId, rules: &ReductionRules) -> T {
+        match self.node(id) {
+            Node::Regular(value) => value,
+            Node::Pair(left, right) => rules.magnitude(self.magnitude_of(left, rules), self.magnitude_of(right, rules)),
+        }
+    }
+
+    fn alloc(&mut self, node: Node) -> NodeId {
+        match self.free.pop() {
+            Some(id) => {
+                self.nodes[id.0] = node;
+                id
+            }
+            None => {
+                self.nodes.push(node);
+                NodeId(self.nodes.len() - 1)
+            }
+        }
+    }
+
+    /// Copies the subtree at `id` into another arena, leaving out the freed slots
+    fn copy_into(&self, id: NodeId, nodes: &mut Vec<Node>) -> NodeId {
+        let node = match self.node(id) {
+            Node::Regular(value) => Node::Regular(value),
+            Node::Pair(left, right) => Node::Pair(self.copy_into(left, nodes), self.copy_into(right, nodes)),
+        };
+        nodes.push(node);
+        NodeId(nodes.len() - 1)
+    }
+
+    /// A random pair which is already reduced under the given rules, like the numbers of the homework
+    pub fn random(rng: &mut impl Rng, rules: &ReductionRules) -> Self {
+        let mut number = Self {
+            nodes: vec![],
+            root: NodeId(0),
+            free: vec![],
+        };
+        number.root = number.random_node(rng, rules, 0);
+        number
+    }
+
+    fn random_node(&mut self, rng: &mut impl Rng, rules: &ReductionRules, depth: usize) -> NodeId {
+        // Always a pair at the root, then less and less likely on the way down
+        let pair = depth == 0 || (depth < rules.explode_depth && rng.gen_bool(0.7 / depth as f64));
+        let node = if pair {
+            let left = self.random_node(rng, rules, depth + 1);
+            let right = self.random_node(rng, rules, depth + 1);
+            Node::Pair(left, right)
+        } else {
+            Node::Regular(rng.gen_range(0..rules.split_threshold.max(1)))
+        };
+        self.alloc(node)
+    }
+
+    /// The two numbers side by side in a pair, before any reduction
+    pub fn pair(left: &Self, right: &Self) -> Self {
+        let mut nodes = Vec::with_capacity(left.nodes.len() + right.nodes.len() + 1);
+        let left_root = left.copy_into(left.root, &mut nodes);
+        let right_root = right.copy_into(right.root, &mut nodes);
+        nodes.push(Node::Pair(left_root, right_root));
+
+        Self {
+            root: NodeId(nodes.len() - 1),
+            nodes,
+            free: vec![],
+        }
+    }
+
+    /// Explodes and splits until neither applies
+    pub fn reduce(&mut self, rules: &ReductionRules) {
+        while self.explode_once(rules) || self.split_once(rules) {}
+    }
+
+    /// Adds two numbers, reducing the sum with the given rules
+    pub fn add_with(&self, rhs: &Self, rules: &ReductionRules) -> Self {
+        let mut sum = Self::pair(self, rhs);
+        sum.reduce(rules);
+        sum
+    }
+
+    /// Adds two numbers, going through the reduction one action at a time
+    pub fn add_traced(&self, rhs: &Self, rules: &ReductionRules) -> Reduction {
+        Reduction {
+            number: Self::pair(self, rhs),
+            rules: *rules,
+        }
+    }
+
+    /// The directions from the root to a node, if it is part of the tree
+    pub fn path_to(&self, target: NodeId) -> Option<Path> {
+        let mut stack = vec![(self.root, vec![])];
+        while let Some((id, path)) = stack.pop() {
+            if id == target {
+                return Some(Path(path));
+            }
+            if let Node::Pair(left, right) = self.node(id) {
+                for (child, direction) in [(right, Direction::Right), (left, Direction::Left)] {
+                    let mut child_path = path.clone();
+                    child_path.push(direction);
+                    stack.push((child, child_path));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Explodes the leftmost pair of regular numbers nested deep enough, if any
+    pub fn explode_once(&mut self, rules: &ReductionRules) -> bool {
+        self.explode(rules).is_some()
+    }
+
+    /// Splits the leftmost regular number over the threshold, if any
+    pub fn split_once(&mut self, rules: &ReductionRules) -> bool {
+        self.split(rules).is_some()
+    }
+
+    /// Explodes like `explode_once`, returning the pair that became 0 and its two values
+    fn explode(&mut self, rules: &ReductionRules) -> Option<(NodeId, T, T)> {
+        // In-order traversal, remembering the last regular number on the left
+        let mut previous = None;
+        let mut stack = vec![(self.root, 0)];
+        while let Some((id, depth)) = stack.pop() {
+            let (left, right) = match self.node(id) {
+                Node::Regular(_) => {
+                    previous = Some(id);
+                    continue;
+                }
+                Node::Pair(left, right) => (left, right),
+            };
+
+            let (Node::Regular(left_value), Node::Regular(right_value)) = (self.node(left), self.node(right)) else {
+                stack.push((right, depth + 1));
+                stack.push((left, depth + 1));
+                continue;
+            };
+            if depth < rules.explode_depth {
+                stack.push((right, depth + 1));
+                stack.push((left, depth + 1));
+                continue;
+            }
+
+            if let Some(previous) = previous {
+                self.add_to_regular(previous, left_value);
+            }
+            // The rest of the traversal leads to the first regular number on the right
+            while let Some((next, _)) = stack.pop() {
+                match self.node(next) {
+                    Node::Regular(_) => {
+                        self.add_to_regular(next, right_value);
+                        break;
+                    }
+                    Node::Pair(next_left, next_right) => {
+                        stack.push((next_right, 0));
+                        stack.push((next_left, 0));
+                    }
+                }
+            }
+
+            self.nodes[id.0] = Node::Regular(0);
+            self.free.extend([right, left]);
+            return Some((id, left_value, right_value));
+        }
+
+        None
+    }
+
+    fn add_to_regular(&mut self, id: NodeId, value: T) {
+        if let Node::Regular(current) = self.node(id) {
+            self.nodes[id.0] = Node::Regular(current + value);
+        }
+    }
+
+    /// Splits like `split_once`, returning the regular number that became a pair and its value
+    fn split(&mut self, rules: &ReductionRules) -> Option<(NodeId, T)> {
+        let mut stack = vec![self.root];
+        while let Some(id) = stack.pop() {
+            match self.node(id) {
+                Node::Regular(value) if value >= rules.split_threshold => {
+                    let (left_value, right_value) = rules.split(value);
+                    let left = self.alloc(Node::Regular(left_value));
+                    let right = self.alloc(Node::Regular(right_value));
+                    self.nodes[id.0] = Node::Pair(left, right);
+                    return Some((id, value));
+                }
+                Node::Regular(_) => (),
+                Node::Pair(left, right) => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Checks that no action of the reduction applies
+    pub fn check_reduced(&self, rules: &ReductionRules) -> std::result::Result<(), Violation> {
+        match self.find_violation(rules) {
+            Some((_, violation)) => Err(violation),
+            None => Ok(()),
+        }
+    }
+
+    /// The leftmost node that the reduction would explode or split, with what is wrong with it
+    fn find_violation(&self, rules: &ReductionRules) -> Option<(NodeId, Violation)> {
+        let mut stack = vec![(self.root, vec![])];
+        while let Some((id, path)) = stack.pop() {
+            match self.node(id) {
+                Node::Pair(..) if path.len() >= rules.explode_depth => {
+                    return Some((id, Violation::TooDeep { path: Path(path) }));
+                }
+                Node::Pair(left, right) => {
+                    for (child, direction) in [(right, Direction::Right), (left, Direction::Left)] {
+                        let mut child_path = path.clone();
+                        child_path.push(direction);
+                        stack.push((child, child_path));
+                    }
+                }
+                Node::Regular(value) if value >= rules.split_threshold => {
+                    return Some((id, Violation::TooLarge { path: Path(path), value }));
+                }
+                Node::Regular(_) => (),
+            }
+        }
+
+        None
+    }
+
+    fn same_as(&self, id: NodeId, other: &Self, other_id: NodeId) -> bool {
+        match (self.node(id), other.node(other_id)) {
+            (Node::Regular(a), Node::Regular(b)) => a == b,
+            (Node::Pair(left, right), Node::Pair(other_left, other_right)) => {
+                self.same_as(left, other, other_left) && self.same_as(right, other, other_right)
+            }
+            _ => false,
+        }
+    }
+
+    fn fmt_node(&self, id: NodeId, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.node(id) {
+            Node::Regular(value) => write!(f, "{}", value),
+            Node::Pair(left, right) => {
+                write!(f, "[")?;
+                self.fmt_node(left, f)?;
+                write!(f, ",")?;
+                self.fmt_node(right, f)?;
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+/// The directions from the root of a number to one of its nodes, written like "LRRL"
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Path(pub Vec<Direction>);
+
+impl Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "root");
+        }
+        for direction in &self.0 {
+            match direction {
+                Direction::Left => write!(f, "L")?,
+                Direction::Right => write!(f, "R")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a number is not reduced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// A pair nested inside as many pairs as the explode depth
+    TooDeep { path: Path },
+    /// A regular number that reaches the split threshold
+    TooLarge { path: Path, value: T },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::TooDeep { path } => {
+                write!(f, "pair at {} is nested inside {} pairs, it should have exploded", path, path.0.len())
+            }
+            Violation::TooLarge { path, value } => {
+                write!(f, "regular number {} at {} should have been split", value, path)
+            }
+        }
+    }
+}
+
+/// A single reduction action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Explode { path: Path, left: T, right: T },
+    Split { path: Path, value: T },
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Explode { path, left, right } => write!(f, "explode [{},{}] at {}", left, right, path),
+            Action::Split { path, value } => write!(f, "split {} at {}", value, path),
+        }
+    }
+}
+
+/// An action with the number it left behind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub action: Action,
+    pub number: SnailfishNumber,
+}
+
+/// Formatted like the worked examples of the puzzle, followed by the details of the action
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self.action {
+            Action::Explode { .. } => "after explode:",
+            Action::Split { .. } => "after split:",
+        };
+        write!(f, "{:<16}{}  # {}", label, self.number, self.action)
+    }
+}
+
+/// The reduction of a number, yielding each action as it is applied
+#[derive(Debug, Clone)]
+pub struct Reduction {
+    number: SnailfishNumber,
+    rules: ReductionRules,
+}
+
+impl Reduction {
+    /// The number as reduced so far
+    pub fn number(&self) -> &SnailfishNumber {
+        &self.number
+    }
+
+    /// Applies the remaining actions and returns the reduced number
+    pub fn finish(mut self) -> SnailfishNumber {
+        self.number.reduce(&self.rules);
+        self.number
+    }
+}
+
+impl Iterator for Reduction {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        let action = if let Some((id, left, right)) = self.number.explode(&self.rules) {
+            let path = self.number.path_to(id)?;
+            Action::Explode { path, left, right }
+        } else if let Some((id, value)) = self.number.split(&self.rules) {
+            let path = self.number.path_to(id)?;
+            Action::Split { path, value }
+        } else {
+            return None;
+        };
+
+        Some(Step {
+            action,
+            number: self.number.clone(),
+        })
+    }
+}
+
+/// Two numbers are equal when they have the same tree, wherever their nodes sit in the arena
+impl PartialEq for SnailfishNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_as(self.root, other, other.root)
+    }
+}
+
+impl Eq for SnailfishNumber {}
+
+impl Display for SnailfishNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_node(self.root, f)
+    }
+}
+
+/// Errors are reported on line 1, the caller knows the actual line number
+impl FromStr for SnailfishNumber {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Parser::parse(s).map(|(number, _)| number)
+    }
+}
+
+/// Checks that a line holds a well-formed number which is already reduced, as the puzzle guarantees
+/// for its inputs. Errors point at the offending character, on line 1
+pub fn validate(line: &str, rules: &ReductionRules) -> Result<SnailfishNumber> {
+    let (number, columns) = Parser::parse(line)?;
+    match number.find_violation(rules) {
+        Some((id, violation)) => Err(ParseError::new(DAY, 1, columns[id.0], violation.to_string())),
+        None => Ok(number),
+    }
+}
+
+/// Builds the tree from the symbols of a line, by recursive descent
+struct Parser {
+    tokens: Vec<(usize, Symbol)>,
+    position: usize,
+    /// The column right after the last character, where a missing symbol is reported
+    end: usize,
+    nodes: Vec<Node>,
+    /// The column where each node starts
+    columns: Vec<usize>,
+}
+
+impl Parser {
+    fn parse(s: &str) -> Result<(SnailfishNumber, Vec<usize>)> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+            end: s.chars().count() + 1,
+            nodes: vec![],
+            columns: vec![],
+        };
+        let root = parser.node()?;
+        if let Some((_, symbol)) = parser.peek() {
+            return Err(parser.error(format!("unexpected '{}' after the number", symbol)));
+        }
+
+        let number = SnailfishNumber {
+            nodes: parser.nodes,
+            root,
+            free: vec![],
+        };
+        Ok((number, parser.columns))
+    }
+
+    fn peek(&self) -> Option<&(usize, Symbol)> {
+        self.tokens.get(self.position)
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        let column = self.peek().map_or(self.end, |(column, _)| *column);
+        ParseError::new(DAY, 1, column, message)
+    }
+
+    fn expect(&mut self, expected: Symbol) -> Result<()> {
+        match self.peek() {
+            Some((_, symbol)) if *symbol == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some((_, symbol)) => Err(self.error(format!("expected '{}', found '{}'", expected, symbol))),
+            None => Err(self.error(format!("expected '{}', found the end of the line", expected))),
+        }
+    }
+
+    fn node(&mut self) -> Result<NodeId> {
+        let column = self.peek().map_or(self.end, |(column, _)| *column);
+        let node = match self.peek() {
+            Some((_, Symbol::LBracket)) => {
+                self.position += 1;
+                let left = self.node()?;
+                self.expect(Symbol::Comma)?;
+                let right = self.node()?;
+                if let Some((_, Symbol::Comma)) = self.peek() {
+                    return Err(self.error("a pair has exactly two elements".to_string()));
+                }
+                self.expect(Symbol::RBracket)?;
+                Node::Pair(left, right)
+            }
+            Some((_, Symbol::Number(value))) => {
+                let value = *value;
+                self.position += 1;
+                Node::Regular(value)
+            }
+            Some((_, symbol)) => return Err(self.error(format!("expected a number, found '{}'", symbol))),
+            None => return Err(self.error("expected a number, found the end of the line".to_string())),
+        };
+
+        self.nodes.push(node);
+        self.columns.push(column);
+        Ok(NodeId(self.nodes.len() - 1))
+    }
+}
+
+impl Add<&SnailfishNumber> for &SnailfishNumber {
+    type Output = SnailfishNumber;
+
+    fn add(self, rhs: &SnailfishNumber) -> SnailfishNumber {
+        self.add_with(rhs, &ReductionRules::default())
+    }
+}
+
+impl Add for SnailfishNumber {
+    type Output = SnailfishNumber;
+
+    fn add(self, rhs: SnailfishNumber) -> SnailfishNumber {
+        &self + &rhs
+    }
+}
+
+/// Adds the numbers one after the other, from the first one.
+///
+/// # Panics
+/// Snailfish addition has no neutral element, so the sum of no number panics
+impl Sum for SnailfishNumber {
+    fn sum<I: Iterator<Item = Self>>(mut iter: I) -> Self {
+        let first = iter.next().expect("Cannot sum an empty list of snailfish numbers");
+        iter.fold(first, |acc, number| acc + number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn number(s: &str) -> SnailfishNumber {
+        s.parse().unwrap()
+    }
+
+    const RANDOM_CASES: usize = 2000;
+
+    #[test]
+    fn test_round_trip() {
+        for s in ["7", "[1,2]", "[[1,2],3]", "[[[[0,7],4],[15,[0,13]]],[1,1]]", "[-3,[12,-40]]"] {
+            assert_eq!(number(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for (s, column) in [("[1,x]", 4), ("[1,2", 5), ("[1;2]", 3), ("[1,2]]", 6), ("", 1), ("[,2]", 2), ("[1,--2]", 4)] {
+            let err = s.parse::<SnailfishNumber>().unwrap_err();
+            assert_eq!((err.day, err.line, err.column), (18, 1, column), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_malformed() {
+        for (s, column, message) in [
+            ("[1,2,3]", 5, "a pair has exactly two elements"),
+            ("[[1,2]", 7, "expected ',', found the end of the line"),
+            ("[1 2]", 3, "unexpected character ' '"),
+            ("[12]", 4, "expected ',', found ']'"),
+        ] {
+            let err = s.parse::<SnailfishNumber>().unwrap_err();
+            assert_eq!((err.column, err.message.as_str()), (column, message), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let rules = ReductionRules::default();
+        assert!(validate("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", &rules).is_ok());
+
+        let err = validate("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]", &rules).unwrap_err();
+        assert_eq!(err.column, 5);
+        assert_eq!(err.message, "pair at LLLL is nested inside 4 pairs, it should have exploded");
+
+        let err = validate("[[1,2],[3,[4,15]]]", &rules).unwrap_err();
+        assert_eq!((err.column, err.message.as_str()), (14, "regular number 15 at RRR should have been split"));
+
+        assert_eq!(
+            number("[10,1]").check_reduced(&rules),
+            Err(Violation::TooLarge {
+                path: Path(vec![Direction::Left]),
+                value: 10
+            })
+        );
+    }
+
+    #[test]
+    fn test_explode() {
+        for (before, after) in [
+            ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+            ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+            ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+            ("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]"),
+        ] {
+            let mut number = number(before);
+            assert!(number.explode_once(&ReductionRules::default()));
+            assert_eq!(number.to_string(), after);
+        }
+        assert!(!number("[[1,2],[3,4]]").explode_once(&ReductionRules::default()));
+    }
+
+    #[test]
+    fn test_split() {
+        let rules = ReductionRules::default();
+        let mut number = number("[11,[10,1]]");
+        assert!(number.split_once(&rules));
+        assert_eq!(number.to_string(), "[[5,6],[10,1]]");
+        assert!(number.split_once(&rules));
+        assert_eq!(number.to_string(), "[[5,6],[[5,5],1]]");
+        assert!(!number.split_once(&rules));
+    }
+
+    #[test]
+    fn test_add() {
+        let sum = number("[[[[4,3],4],4],[7,[[8,4],9]]]") + number("[1,1]");
+        assert_eq!(sum.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+        assert_eq!(sum, number("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"));
+    }
+
+    #[test]
+    fn test_trace() {
+        let rules = ReductionRules::default();
+        let mut reduction = number("[[[[4,3],4],4],[7,[[8,4],9]]]").add_traced(&number("[1,1]"), &rules);
+        assert_eq!(reduction.number().to_string(), "[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+
+        let steps = reduction.by_ref().map(|step| step.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            [
+                "after explode:  [[[[0,7],4],[7,[[8,4],9]]],[1,1]]  # explode [4,3] at LLLL",
+                "after explode:  [[[[0,7],4],[15,[0,13]]],[1,1]]  # explode [8,4] at LRRL",
+                "after split:    [[[[0,7],4],[[7,8],[0,13]]],[1,1]]  # split 15 at LRL",
+                "after split:    [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]  # split 13 at LRRR",
+                "after explode:  [[[[0,7],4],[[7,8],[6,0]]],[8,1]]  # explode [6,7] at LRRR",
+            ]
+        );
+        assert_eq!(reduction.finish(), number("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"));
+        assert_eq!(number("12").path_to(NodeId(0)).unwrap().to_string(), "root");
+    }
+
+    #[test]
+    fn test_sum() {
+        let sum = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"]
+            .into_iter()
+            .map(number)
+            .sum::<SnailfishNumber>();
+        assert_eq!(sum.to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = ReductionRules {
+            explode_depth: 2,
+            split_threshold: 6,
+            split_rounding: Rounding::Up,
+            magnitude_weights: (1, 1),
+        };
+        let sum = number("[1,2]").add_with(&number("[3,[4,5]]"), &rules);
+        // [[1,2],[7,0]] -> [[1,2],[[4,3],0]] -> [[1,6],[0,3]] -> [[1,[3,3]],[0,3]] -> [[4,0],[3,3]]
+        assert_eq!(sum.to_string(), "[[4,0],[3,3]]");
+        assert_eq!(sum.magnitude_with(&rules), 10);
+        assert_eq!(rules.split(11), (6, 5));
+    }
+
+    #[test]
+    fn test_random_is_reduced() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let shallow = ReductionRules {
+            explode_depth: 2,
+            split_threshold: 4,
+            ..Default::default()
+        };
+        for rules in [ReductionRules::default(), shallow] {
+            for _ in 0..RANDOM_CASES {
+                let number = SnailfishNumber::random(&mut rng, &rules);
+                assert_eq!(number.check_reduced(&rules), Ok(()), "{}", number);
+                assert!(matches!(number.node(number.root()), Node::Pair(..)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sum_is_reduced() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let rules = ReductionRules::default();
+        for _ in 0..RANDOM_CASES {
+            let a = SnailfishNumber::random(&mut rng, &rules);
+            let b = SnailfishNumber::random(&mut rng, &rules);
+            let sum = &a + &b;
+            assert_eq!(sum.check_reduced(&rules), Ok(()), "{} + {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_magnitude_is_positive() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let rules = ReductionRules::default();
+        for _ in 0..RANDOM_CASES {
+            let number = SnailfishNumber::random(&mut rng, &rules);
+            let any_positive = number.to_string().contains(|c: char| ('1'..='9').contains(&c));
+            match any_positive {
+                true => assert!(number.magnitude() > 0, "{}", number),
+                false => assert_eq!(number.magnitude(), 0, "{}", number),
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_round_trip() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let rules = ReductionRules::default();
+        for _ in 0..RANDOM_CASES {
+            let random = SnailfishNumber::random(&mut rng, &rules);
+            let parsed = number(&random.to_string());
+            assert_eq!(parsed, random);
+            assert_eq!(parsed.to_string(), random.to_string());
+        }
+    }
+
+    #[test]
+    fn test_magnitude() {
+        assert_eq!(number("[[1,2],[[3,4],5]]").magnitude(), 143);
+        assert_eq!(number("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").magnitude(), 3488);
+    }
+}
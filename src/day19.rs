@@ -1,201 +1,373 @@
-use std::collections::{HashMap, HashSet};
-use std::fs::read_to_string;
-use std::io::{self, Write};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Display;
 use std::sync::LazyLock; 
 
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::runner::Input;
+use crate::{Answer, Day, ParseError, Result};
+
+pub mod export;
+pub mod synthetic;
+
 pub struct Solution {
-    content: String,
-    offsets: Vec<Vector>,
+    scanners: Scanners,
+    config: AlignmentConfig,
+    placement: Option<Placement>,
+}
+
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
+        Self::parse_with(input, &AlignmentConfig::default())
+    }
+
+    fn part1(&mut self) -> Answer {
+        self.answer(|component| component.beacons().len())
+    }
+
+    fn part2(&mut self) -> Answer {
+        // We need to find the max distance between two scanners
+        self.answer(Component::max_distance)
+    }
 }
 
 impl Solution {
-    pub fn init() -> Self {
-        Self {
-            content: read_to_string("inputs/day19.txt").unwrap(),
-            offsets: vec![],
+    /// Only reads the scanners, aligning them is left to the first part so that it runs under the timeout
+    pub fn parse_with(input: &str, config: &AlignmentConfig) -> Result<Self> {
+        let scanners = Scanners::try_from(input.to_string())?;
+        if scanners.0.is_empty() {
+            return Err(ParseError::new(DAY, 1, 1, "expected at least one scanner"));
         }
+
+        Ok(Self {
+            scanners,
+            config: *config,
+            placement: None,
+        })
     }
 
-    fn part1(&mut self) -> usize {
-        // Parse
-        let scanners = Scanners::try_from(self.content.clone()).unwrap();
-        let (mapped_scanners, offsets) = mapscanners(&scanners);
-        self.offsets = offsets;
-        mapped_scanners.0
-            .values()
-            .fold(HashSet::new(), |acc, scanner| acc.union(scanner).copied().collect::<HashSet<_>>())
-            .len()
+    // Both parts need the scanners aligned, which is the expensive part, so it's only done once
+    pub fn placement(&mut self) -> &Placement {
+        self.placement.get_or_insert_with(|| place(&self.scanners, &self.config))
     }
 
-    fn part2(&mut self) -> Coordinate {
-        // We need to find the max distance between two beacons
-        itertools::iproduct!(self.offsets.iter(), self.offsets.iter())
-            .map(|(a, b)| manhattan_distance(a, b))
-            .max()
-            .unwrap()
+    /// The placement, unless some scanners can't be aligned with the others and the config doesn't ask for
+    /// each group of overlapping scanners to be solved separately. The error names these groups
+    pub fn solvable_placement(&mut self) -> std::result::Result<&Placement, String> {
+        let separate = self.config.separate_components;
+        let placement = self.placement();
+        if placement.is_complete() || separate {
+            Ok(placement)
+        } else {
+            Err(placement.to_string())
+        }
     }
 
-    pub fn solve(&mut self) {
-        println!("========= DAY 19 ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
+    /// The rotation and translation of each scanner relative to scanner 0, or the lowest scanner id
+    pub fn alignment(&mut self) -> &Alignment {
+        self.placement().components[0].alignment()
+    }
 
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
+    /// The answer of the only component, or the answer of each component relative to its lowest scanner id.
+    /// Parts can't return errors, so an input whose scanners can't all be aligned makes the part fail
+    fn answer<A: Display + Send + 'static>(&mut self, answer: fn(&Component) -> A) -> Answer {
+        let placement = self
+            .solvable_placement()
+            .unwrap_or_else(|unalignable| panic!("Invalid input: {}", unalignable));
+        match placement.components.as_slice() {
+            [component] => Box::new(answer(component)),
+            components => Box::new(
+                components
+                    .iter()
+                    .map(|component| format!("scanner {}: {}", component.reference, answer(component)))
+                    .join(", "),
+            ),
+        }
+    }
+}
 
-        print!("Solving part 2: ");
-        io::stdout().flush().unwrap();
-        let start = std::time::Instant::now();
-        let part2 = self.part2();
-        let part2_time = start.elapsed();
-        println!("{:?} (took {:?})", part2, part2_time);
-        println!();
+pub const COMPONENTS_USAGE: &str = "Usage: advent 19 components [--input <file>|-]";
+
+/// `advent 19 components [--input <file>|-]`: the answers of each group of overlapping scanners,
+/// relative to its lowest scanner id, for inputs whose scanners can't all be aligned
+pub fn components(args: &[String], out: &mut dyn std::io::Write) -> std::result::Result<(), String> {
+    let input = tool_input(args, COMPONENTS_USAGE)?;
+    let config = AlignmentConfig {
+        separate_components: true,
+        ..AlignmentConfig::default()
+    };
+    let mut solution = Solution::parse_with(&input, &config).map_err(|err| format!("Invalid input: {}", err))?;
+
+    for component in &solution.placement().components {
+        writeln!(
+            out,
+            "scanner {}: scanners {}, {} beacons, largest distance {}",
+            component.reference,
+            component.scanners().iter().join(" "),
+            component.beacons().len(),
+            component.max_distance()
+        )
+        .map_err(|err| format!("Could not write to stdout: {}", err))?;
     }
+    Ok(())
+}
+
+/// Reads the input of a day 19 command from `[--input <file>|-]`, or the default input
+fn tool_input(args: &[String], usage: &str) -> std::result::Result<String, String> {
+    let input = match args {
+        [] => Input::Default,
+        [flag, path] if flag == "-i" || flag == "--input" => match path.as_str() {
+            "-" => Input::Stdin,
+            path => Input::File(path.into()),
+        },
+        _ => return Err(usage.to_string()),
+    };
+    input
+        .read(DAY)
+        .map_err(|err| format!("Could not read the input of day {}: {}", DAY, err))
 }
 
-type Coordinate = i32; // The type used to represent a coordinate value
-type Vector = (Coordinate, Coordinate, Coordinate); // A vector is a tuple of (x, y, z)
-type ScannerId = usize; // The type used to represent a scanner id
-type Beacon = Vector; // A beacon is a represented by it's position
+pub type Coordinate = i32; // The type used to represent a coordinate value
+pub type Vector = (Coordinate, Coordinate, Coordinate); // A vector is a tuple of (x, y, z)
+pub type ScannerId = usize; // The type used to represent a scanner id
+pub type Beacon = Vector; // A beacon is a represented by it's position
 type Scanner = HashSet<Beacon>; // A scanner is a set of beacons
-type Basis = (Vector, Vector, Vector); // A basis is a tuple of three coordinates
+type Fingerprint = i64; // The squared distance between two beacons of a scanner, too large for a coordinate
+type Fingerprints = HashMap<Fingerprint, Vec<(Beacon, Beacon)>>; // The pairs of beacons having each fingerprint
+
+const DAY: u8 = 19;
+const SCANNER_PREFIX: &str = "--- scanner ";
 
 #[derive(Debug)]
-struct Scanners(HashMap<ScannerId, Scanner>); // A collection of scanners is accessed by their id
-
-const ROTATIONS: [[Coordinate; 3]; 24] = [
-    [0,   0,   0], [90,   0,   0], [180,   0,   0], [270,   0,   0],
-    [0,  90,   0], [90,  90,   0], [180,  90,   0], [270,  90,   0],
-    [0, 180,   0], [90, 180,   0], [180, 180,   0], [270, 180,   0],
-    [0, 270,   0], [90, 270,   0], [180, 270,   0], [270, 270,   0],
-    [0,   0,  90], [90,   0,  90], [180,   0,  90], [270,   0,  90],
-    [0,   0, 270], [90,   0, 270], [180,   0, 270], [270,   0, 270]
-];
-
-static BASES: LazyLock<Vec<Basis>> = LazyLock::new(|| {
-    ROTATIONS.iter().map(|rotation| {
+pub struct Scanners(HashMap<ScannerId, Scanner>); // A collection of scanners is accessed by their id
+
+/// When two scanners are considered aligned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlignmentConfig {
+    /// The number of beacons two scanners must see in common, at least 2: the offset between two scanners is
+    /// found by matching a pair of beacons, so scanners sharing a single beacon are never aligned
+    pub min_overlap: usize,
+    /// How far a scanner sees along each axis. When set, two scanners are only aligned if every beacon
+    /// within the range of both is reported by both
+    pub sensor_range: Option<Coordinate>,
+    /// Solves each group of overlapping scanners relative to its lowest scanner id, instead of rejecting an input
+    /// whose scanners can't all be aligned
+    pub separate_components: bool,
+}
+
+impl Default for AlignmentConfig {
+    /// The rules of the puzzle, which guarantees the 1000 range so it doesn't need to be checked
+    fn default() -> Self {
+        Self {
+            min_overlap: 12,
+            sensor_range: None,
+            separate_components: false,
+        }
+    }
+}
+
+impl AlignmentConfig {
+    /// The number of pairs among `min_overlap` beacons, whose fingerprints two aligned scanners share
+    fn min_shared_fingerprints(&self) -> usize {
+        self.min_overlap * self.min_overlap.saturating_sub(1) / 2
+    }
+
+    /// Whether the beacons both scanners could see are seen by both, `b` being already moved to the frame of `a`
+    fn consistent(&self, a: &Scanner, a_position: Vector, b: &Scanner, b_position: Vector) -> bool {
+        let Some(range) = self.sensor_range else {
+            return true;
+        };
+        let visible = |beacon: &Beacon, (x, y, z): Vector| {
+            (beacon.0 - x).abs() <= range && (beacon.1 - y).abs() <= range && (beacon.2 - z).abs() <= range
+        };
+
+        a.iter()
+            .chain(b.iter())
+            .filter(|beacon| visible(beacon, a_position) && visible(beacon, b_position))
+            .all(|beacon| a.contains(beacon) && b.contains(beacon))
+    }
+}
+
+/// The 24 orientations a scanner can have
+static ROTATIONS: LazyLock<Vec<Rotation>> = LazyLock::new(Rotation::all);
+
+/// A proper rotation of the grid, as an integer matrix applied to column vectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation([[Coordinate; 3]; 3]);
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    /// The rotations keeping the axes on the axes: the signed permutation matrices of determinant 1, identity first
+    pub fn all() -> Vec<Rotation> {
+        (0..3)
+            .permutations(3)
+            .cartesian_product(itertools::iproduct!([1, -1], [1, -1], [1, -1]))
+            .map(|(axes, (sx, sy, sz))| {
+                let mut matrix = [[0; 3]; 3];
+                for (row, sign) in [sx, sy, sz].into_iter().enumerate() {
+                    matrix[row][axes[row]] = sign;
+                }
+                Rotation(matrix)
+            })
+            .filter(|rotation| rotation.determinant() == 1)
+            .collect()
+    }
+
+    /// The rows of the matrix
+    pub fn matrix(&self) -> [[Coordinate; 3]; 3] {
+        self.0
+    }
+
+    pub fn determinant(&self) -> Coordinate {
+        let [[a, b, c], [d, e, f], [g, h, i]] = self.0;
+        a * (e * i - f * h) - b * (d * i - f * g) + c * (d * h - e * g)
+    }
+
+    pub fn apply(&self, (x, y, z): Vector) -> Vector {
+        let [r1, r2, r3] = self.0;
         (
-            rotation_xyz((1, 0, 0), rotation[0], rotation[1], rotation[2]),
-            rotation_xyz((0, 1, 0), rotation[0], rotation[1], rotation[2]),
-            rotation_xyz((0, 0, 1), rotation[0], rotation[1], rotation[2]),
+            r1[0] * x + r1[1] * y + r1[2] * z,
+            r2[0] * x + r2[1] * y + r2[2] * z,
+            r3[0] * x + r3[1] * y + r3[2] * z,
         )
-    }).collect_vec()
-});
+    }
+
+    /// The rotation applying `other` first, then `self`
+    pub fn compose(&self, other: &Rotation) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, column) in itertools::iproduct!(0..3, 0..3) {
+            matrix[row][column] = (0..3).map(|k| self.0[row][k] * other.0[k][column]).sum();
+        }
+        Rotation(matrix)
+    }
+
+    /// Rotations are orthogonal, so their inverse is their transpose
+    pub fn inverse(&self) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, column) in itertools::iproduct!(0..3, 0..3) {
+            matrix[row][column] = self.0[column][row];
+        }
+        Rotation(matrix)
+    }
+}
 
 impl TryFrom<String> for Scanners {
-    type Error = Box<dyn std::error::Error>;
+    type Error = ParseError;
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
         let mut scanners = HashMap::new();
+        let mut current: Option<(ScannerId, Scanner)> = None;
+
+        for (i, line) in value.lines().enumerate() {
+            let error = |column: usize, message: String| ParseError::new(DAY, i + 1, column, message);
 
-        // Separate the different blocks
-        for block in value.split("\n\n") {
-            let lines = block.lines().collect_vec();
-
-            // Read the scanner ID 
-            let scanner_id = lines[0]
-                .strip_prefix("--- scanner ").ok_or("Invalid scanner ID")?
-                .strip_suffix(" ---").ok_or("Invalid scanner ID")?
-                .parse()?;
-
-            // Read the scanner beacons
-            let mut scanner = Scanner::new();
-            for line in &lines[1..] {
-                let beacon = line
-                    .split(",")
-                    .map(|s| s.parse().unwrap())
-                    .collect_tuple()
-                    .ok_or("Invalid beacon")?;
-                scanner.insert(beacon);
+            // Blocks are separated by empty lines
+            if line.is_empty() {
+                scanners.extend(current.take());
+                continue;
+            }
+
+            // Read the scanner ID
+            if let Some(header) = line.strip_prefix(SCANNER_PREFIX) {
+                scanners.extend(current.take());
+                let scanner_id: ScannerId = header
+                    .strip_suffix(" ---")
+                    .ok_or_else(|| error(line.len(), "expected the header to end with ' ---'".to_string()))?
+                    .parse()
+                    .map_err(|_| error(SCANNER_PREFIX.len() + 1, format!("invalid scanner ID in '{}'", line)))?;
+                if scanners.contains_key(&scanner_id) {
+                    return Err(error(SCANNER_PREFIX.len() + 1, format!("scanner {} is defined twice", scanner_id)));
+                }
+                current = Some((scanner_id, Scanner::new()));
+                continue;
             }
-            scanners.insert(scanner_id, scanner);
+
+            // Read a beacon of the current scanner
+            let (_, scanner) = current
+                .as_mut()
+                .ok_or_else(|| error(1, "expected a '--- scanner N ---' header".to_string()))?;
+            let mut coordinates = vec![];
+            let mut column = 1;
+            for field in line.split(',') {
+                let coordinate = field
+                    .parse()
+                    .map_err(|_| error(column, format!("invalid coordinate '{}'", field)))?;
+                coordinates.push(coordinate);
+                column += field.len() + 1;
+            }
+            let beacon = coordinates
+                .into_iter()
+                .collect_tuple()
+                .ok_or_else(|| error(1, format!("expected 3 coordinates in '{}'", line)))?;
+            scanner.insert(beacon);
         }
+        scanners.extend(current);
 
         Ok(Self(scanners))
     }
 }
 
-fn cos(angle: Coordinate) -> Coordinate {
-    match angle {
-        0 => 1,
-        90 => 0,
-        180 => -1,
-        270 => 0,
-        _ => panic!("Invalid angle"),
-    }
+fn translate_scanner(scanner: &Scanner, rotation: &Rotation) -> Scanner {
+    scanner.iter().map(|beacon| rotation.apply(*beacon)).collect()
 }
 
-fn sin(angle: Coordinate) -> Coordinate {
-    match angle {
-        0 => 0,
-        90 => 1,
-        180 => 0,
-        270 => -1,
-        _ => panic!("Invalid angle"),
+/// Squared distances between pairs of beacons don't depend on the position or orientation of the scanner,
+/// so two scanners seeing the same beacons share the fingerprints of every pair of them
+fn fingerprints(scanner: &Scanner) -> Fingerprints {
+    let mut fingerprints = Fingerprints::new();
+    for (a, b) in scanner.iter().tuple_combinations() {
+        let square = |a: Coordinate, b: Coordinate| (a as i64 - b as i64).pow(2);
+        let fingerprint = square(a.0, b.0) + square(a.1, b.1) + square(a.2, b.2);
+        fingerprints.entry(fingerprint).or_default().push((*a, *b));
     }
+    fingerprints
 }
 
-fn rotation_x((x, y, z): Vector, angle: Coordinate) -> Vector {
-    (
-        x,
-        y * cos(angle) - z * sin(angle),
-        y * sin(angle) + z * cos(angle),
-    )
-}
-
-fn rotation_y((x, y, z): Vector, angle: Coordinate) -> Vector {
-    (
-        x * cos(angle) + z * sin(angle),
-        y,
-        -x * sin(angle) + z * cos(angle),
-    )
+/// The number of beacon pairs of `a` that could be matched with a pair of `b` having the same fingerprint
+fn shared_fingerprints(a: &Fingerprints, b: &Fingerprints) -> usize {
+    a.iter()
+        .filter_map(|(fingerprint, pairs)| b.get(fingerprint).map(|other| pairs.len().min(other.len())))
+        .sum()
 }
 
-fn rotation_z((x, y, z): Vector, angle: Coordinate) -> Vector {
-    (
-        x * cos(angle) - y * sin(angle),
-        x * sin(angle) + y * cos(angle),
-        z,
-    )
-}
-
-fn rotation_xyz(mut vector: Vector, angle_x: Coordinate, angle_y: Coordinate, angle_z: Coordinate) -> Vector {
-    vector = rotation_x(vector, angle_x);
-    vector = rotation_y(vector, angle_y);
-    rotation_z(vector, angle_z)
-}
-
-fn translate_beacon(beacon: Beacon, basis: Basis) -> Beacon {
-    let (bx1, by1, bz1) = basis.0;
-    let (bx2, by2, bz2) = basis.1;
-    let (bx3, by3, bz3) = basis.2;
-    let (x, y, z) = beacon;
-
-    (
-        bx1 * x + by1 * y + bz1 * z,
-        bx2 * x + by2 * y + bz2 * z,
-        bx3 * x + by3 * y + bz3 * z,
-    )
-}
+/// Finds how to move `b` to the frame of `a`, `a` being at `a_position` in that frame
+fn incommon(
+    a: &Scanner,
+    a_prints: &Fingerprints,
+    a_position: Vector,
+    b: &Scanner,
+    b_prints: &Fingerprints,
+    config: &AlignmentConfig,
+) -> Option<(Scanner, Transform)> {
+    if shared_fingerprints(a_prints, b_prints) < config.min_shared_fingerprints() {
+        return None;
+    }
 
-fn translate_scanner(scanner: &Scanner, basis: Basis) -> Scanner {
-    scanner.iter().map(|beacon| translate_beacon(*beacon, basis)).collect()
-}
+    for rotation in ROTATIONS.iter() {
+        // Only the offsets moving a pair of `b` onto a pair of `a` with the same fingerprint are worth checking
+        let mut offsets = HashSet::new();
+        for (fingerprint, a_pairs) in a_prints {
+            let Some(b_pairs) = b_prints.get(fingerprint) else {
+                continue;
+            };
+            for ((a1, a2), (b1, b2)) in itertools::iproduct!(a_pairs, b_pairs) {
+                let (b1, b2) = (rotation.apply(*b1), rotation.apply(*b2));
+                for (b1, b2) in [(b1, b2), (b2, b1)] {
+                    let offset = (a1.0 - b1.0, a1.1 - b1.1, a1.2 - b1.2);
+                    if offset == (a2.0 - b2.0, a2.1 - b2.1, a2.2 - b2.2) {
+                        offsets.insert(offset);
+                    }
+                }
+            }
+        }
+        if offsets.is_empty() {
+            continue;
+        }
 
-fn incommon(a: &Scanner, b: &Scanner) -> Option<(Scanner, Vector)> {
-    for basis in BASES.iter() {
-        let translated_b = translate_scanner(b, *basis);
-        for (a_beacon, b_beacon) in itertools::iproduct!(a.iter(), translated_b.iter()) {
-            let offset = (
-                a_beacon.0 - b_beacon.0,
-                a_beacon.1 - b_beacon.1,
-                a_beacon.2 - b_beacon.2,
-            );
+        let translated_b = translate_scanner(b, rotation);
+        for offset in offsets {
             let shifted_b: Scanner = translated_b.iter().map(|beacon| {
                 (
                     beacon.0 + offset.0,
@@ -203,8 +375,10 @@ fn incommon(a: &Scanner, b: &Scanner) -> Option<(Scanner, Vector)> {
                     beacon.2 + offset.2,
                 )
             }).collect();
-            if a.intersection(&shifted_b).count() >= 12 {
-                return Some((shifted_b, offset))
+            if a.intersection(&shifted_b).count() >= config.min_overlap
+                && config.consistent(a, a_position, &shifted_b, offset)
+            {
+                return Some((shifted_b, Transform { rotation: *rotation, translation: offset }))
             }
         }
     }
@@ -212,37 +386,372 @@ fn incommon(a: &Scanner, b: &Scanner) -> Option<(Scanner, Vector)> {
     None
 }
 
-fn mapscanners(scanners: &Scanners) -> (Scanners, Vec<Vector>) {
-    let mut mapped_scanners = HashMap::new();
-    mapped_scanners.insert(0, scanners.0[&0].clone());
-    let mut offsets = vec![(0, 0, 0)];
-    let mut searched = HashSet::new();
+/// Moves points from the frame of a scanner to the frame of the reference scanner, rotating then translating them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub translation: Vector,
+}
 
-    while scanners.0.keys().collect::<HashSet<_>>().difference(&mapped_scanners.keys().collect::<HashSet<_>>()).count() > 0 {
-        for id1 in scanners.0.keys() {
-            if searched.contains(id1) || !mapped_scanners.contains_key(id1) {
-                continue;
-            }
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        rotation: Rotation::IDENTITY,
+        translation: (0, 0, 0),
+    };
 
-            for (id2, scanner2) in scanners.0.iter() {
-                if mapped_scanners.contains_key(id2) || id1 == id2 {
-                    continue;
-                }
+    pub fn apply(&self, point: Vector) -> Vector {
+        let (x, y, z) = self.rotation.apply(point);
+        (x + self.translation.0, y + self.translation.1, z + self.translation.2)
+    }
 
-                if let Some((common, offset)) = incommon(mapped_scanners.get(id1).unwrap(), scanner2) {
-                    mapped_scanners.insert(*id2, common);
-                    offsets.push(offset);
-                    // println!("Found common beacons between scanner {} and scanner {} => {} remaining", id1, id2, scanners.0.keys().collect::<HashSet<_>>().difference(&mapped_scanners.keys().collect::<HashSet<_>>()).count());
-                }
-            }
+    /// Moves points from the frame of the reference scanner back to the frame of the scanner
+    pub fn inverse(&self) -> Transform {
+        let rotation = self.rotation.inverse();
+        let (x, y, z) = rotation.apply(self.translation);
+        Transform {
+            rotation,
+            translation: (-x, -y, -z),
+        }
+    }
+}
+
+/// The rotation and translation of each scanner of a component relative to its reference scanner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment {
+    pub reference: ScannerId,
+    pub transforms: BTreeMap<ScannerId, Transform>,
+}
+
+impl Alignment {
+    pub fn transform(&self, id: ScannerId) -> Option<&Transform> {
+        self.transforms.get(&id)
+    }
+
+    /// The position of a scanner in the frame of the reference
+    pub fn position(&self, id: ScannerId) -> Option<Vector> {
+        self.transform(id).map(|transform| transform.translation)
+    }
+
+    /// Moves a point seen by scanner `id` to the frame of the reference
+    pub fn to_reference(&self, id: ScannerId, point: Vector) -> Option<Vector> {
+        self.transform(id).map(|transform| transform.apply(point))
+    }
+
+    /// Moves a point of the frame of the reference to the frame of scanner `id`
+    pub fn from_reference(&self, id: ScannerId, point: Vector) -> Option<Vector> {
+        self.transform(id).map(|transform| transform.inverse().apply(point))
+    }
+
+    /// Moves a point seen by scanner `from` to the frame of scanner `to`, through the frame of the reference
+    pub fn convert(&self, from: ScannerId, to: ScannerId, point: Vector) -> Option<Vector> {
+        self.from_reference(to, self.to_reference(from, point)?)
+    }
+}
+
+/// The scanners aligned with a reference scanner, with their beacons in its frame
+#[derive(Debug)]
+pub struct Component {
+    pub reference: ScannerId,
+    mapped: Scanners,
+    alignment: Alignment,
+}
+
+impl Component {
+    pub fn scanners(&self) -> Vec<ScannerId> {
+        self.mapped.0.keys().copied().sorted().collect()
+    }
+
+    pub fn alignment(&self) -> &Alignment {
+        &self.alignment
+    }
+
+    pub fn beacons(&self) -> HashSet<Beacon> {
+        self.mapped.0.values().flatten().copied().collect()
+    }
+
+    /// The largest manhattan distance between two scanners of the component
+    pub fn max_distance(&self) -> Coordinate {
+        let positions = self.alignment.transforms.values().map(|transform| transform.translation).collect_vec();
+        itertools::iproduct!(positions.iter(), positions.iter())
+            .map(|(a, b)| manhattan_distance(a, b))
+            .max()
+            .unwrap_or(0)
+    }
+}
 
-            searched.insert(id1);
+/// The connected components of the overlap graph of the scanners, in the order of their lowest scanner id.
+/// Each component is placed relative to its own lowest scanner id, so the scanners outside the first one
+/// couldn't be aligned with the lowest id of all
+#[derive(Debug)]
+pub struct Placement {
+    pub components: Vec<Component>,
+}
+
+impl Placement {
+    pub fn is_complete(&self) -> bool {
+        self.components.len() <= 1
+    }
+
+    pub fn unplaced(&self) -> Vec<ScannerId> {
+        self.components.iter().skip(1).flat_map(Component::scanners).sorted().collect()
+    }
+}
+
+impl Display for Placement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.components.first() {
+            None => write!(f, "no scanners"),
+            Some(_) if self.is_complete() => write!(f, "all scanners are aligned"),
+            Some(first) => write!(
+                f,
+                "scanners {} can't be aligned with scanner {}, the scanners overlap in groups {}",
+                self.unplaced().iter().join(", "),
+                first.reference,
+                self.components
+                    .iter()
+                    .map(|component| format!("[{}]", component.scanners().iter().join(", ")))
+                    .join(", ")
+            ),
         }
     }
+}
 
-    (Scanners(mapped_scanners), offsets)
+/// Aligns every scanner it can with the lowest scanner id, then the remaining ones with the lowest of them, and so on
+pub fn place(scanners: &Scanners, config: &AlignmentConfig) -> Placement {
+    let prints: HashMap<_, _> = scanners.0.iter().map(|(id, scanner)| (*id, fingerprints(scanner))).collect();
+    let mut unplaced: BTreeSet<_> = scanners.0.keys().copied().collect();
+    let mut components = vec![];
+    while let Some(reference) = unplaced.first().copied() {
+        components.push(mapscanners(scanners, &prints, reference, &mut unplaced, config));
+    }
+
+    Placement { components }
+}
+
+/// Aligns the `unplaced` scanners that overlap `reference`, directly or through other scanners, and removes them
+fn mapscanners(
+    scanners: &Scanners,
+    prints: &HashMap<ScannerId, Fingerprints>,
+    reference: ScannerId,
+    unplaced: &mut BTreeSet<ScannerId>,
+    config: &AlignmentConfig,
+) -> Component {
+    unplaced.remove(&reference);
+    let mut mapped_scanners = HashMap::from([(reference, scanners.0[&reference].clone())]);
+    // Mapping moves the beacons, so the mapped scanners need fingerprints of their own
+    let mut mapped_prints = HashMap::from([(reference, prints[&reference].clone())]);
+    let mut transforms = BTreeMap::from([(reference, Transform::IDENTITY)]);
+
+    // Each mapped scanner is searched once for the unplaced scanners it overlaps, which ends once none is left to search
+    let mut searching = VecDeque::from([reference]);
+    while let Some(id1) = searching.pop_front() {
+        let (a, a_prints, a_position) = (&mapped_scanners[&id1], &mapped_prints[&id1], transforms[&id1].translation);
+        let found = unplaced
+            .iter()
+            .filter_map(|id2| {
+                incommon(a, a_prints, a_position, &scanners.0[id2], &prints[id2], config).map(|common| (*id2, common))
+            })
+            .collect_vec();
+
+        for (id2, (common, transform)) in found {
+            unplaced.remove(&id2);
+            mapped_prints.insert(id2, fingerprints(&common));
+            mapped_scanners.insert(id2, common);
+            transforms.insert(id2, transform);
+            searching.push_back(id2);
+        }
+    }
+
+    Component {
+        reference,
+        mapped: Scanners(mapped_scanners),
+        alignment: Alignment { reference, transforms },
+    }
 }
 
 fn manhattan_distance(a: &Vector, b: &Vector) -> Coordinate {
     (a.0 - b.0).abs() + (a.1 - b.1).abs() + (a.2 - b.2).abs()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../inputs/examples/day19.txt");
+
+    fn placement(input: &str, config: &AlignmentConfig) -> Placement {
+        place(&Scanners::try_from(input.to_string()).unwrap(), config)
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = Solution::parse("--- scanner 0 ---\n1,2,3\n4,x,6").err().unwrap();
+        assert_eq!((err.day, err.line, err.column), (19, 3, 3));
+
+        let err = Solution::parse("--- scanner 0 ---\n1,2").err().unwrap();
+        assert_eq!((err.line, err.column), (2, 1));
+
+        let err = Solution::parse("1,2,3").err().unwrap();
+        assert_eq!((err.line, err.column), (1, 1));
+
+        let err = Solution::parse("--- scanner a ---").err().unwrap();
+        assert_eq!((err.line, err.column), (1, 13));
+    }
+
+    #[test]
+    fn test_fingerprints() {
+        let scanners = Scanners::try_from(EXAMPLE.to_string()).unwrap();
+        let prints: HashMap<_, _> = scanners.0.iter().map(|(id, scanner)| (*id, fingerprints(scanner))).collect();
+        let pairs = scanners.0[&0].len() * (scanners.0[&0].len() - 1) / 2;
+        assert_eq!(prints[&0].values().map(Vec::len).sum::<usize>(), pairs);
+
+        // Scanners 0 and 1 overlap, 0 and 2 don't
+        let config = AlignmentConfig::default();
+        assert!(shared_fingerprints(&prints[&0], &prints[&1]) >= config.min_shared_fingerprints());
+        assert!(shared_fingerprints(&prints[&0], &prints[&2]) < config.min_shared_fingerprints());
+        let origin = (0, 0, 0);
+        assert!(incommon(&scanners.0[&0], &prints[&0], origin, &scanners.0[&2], &prints[&2], &config).is_none());
+
+        let (_, transform) =
+            incommon(&scanners.0[&0], &prints[&0], origin, &scanners.0[&1], &prints[&1], &config).unwrap();
+        assert_eq!(transform.translation, (68, -1246, -43));
+
+        // Squared distances outgrow the coordinates
+        let far = Scanner::from([(-30000, -30000, -30000), (30000, 30000, 30000)]);
+        assert_eq!(fingerprints(&far).keys().copied().collect_vec(), [3 * 60000i64.pow(2)]);
+    }
+
+    #[test]
+    fn test_unalignable() {
+        // Scanner 2 only overlaps scanner 4, which is left out
+        let input = EXAMPLE.split("\n\n").take(3).join("\n\n");
+        let placement = placement(&input, &AlignmentConfig::default());
+        assert!(!placement.is_complete());
+        assert_eq!(placement.unplaced(), [2]);
+        let components = placement.components.iter().map(Component::scanners).collect_vec();
+        assert_eq!(components, [vec![0, 1], vec![2]]);
+        assert_eq!(
+            placement.to_string(),
+            "scanners 2 can't be aligned with scanner 0, the scanners overlap in groups [0, 1], [2]"
+        );
+
+        // Rejected by the parts, unless the components are solved separately
+        let mut solution = Solution::parse(&input).unwrap();
+        let err = solution.solvable_placement().err().unwrap();
+        assert!(err.starts_with("scanners 2 can't be aligned"));
+        let panic = std::panic::catch_unwind(AssertUnwindSafe(|| solution.part1())).err().unwrap();
+        assert!(panic.downcast_ref::<String>().unwrap().starts_with("Invalid input: scanners 2"));
+        let err = Solution::parse("").err().unwrap();
+        assert_eq!((err.line, err.column), (1, 1));
+
+        let separate = AlignmentConfig {
+            separate_components: true,
+            ..AlignmentConfig::default()
+        };
+        let mut solution = Solution::parse_with(&input, &separate).unwrap();
+        assert_eq!(solution.part1().to_string(), "scanner 0: 38, scanner 2: 26");
+        assert_eq!(solution.part2().to_string(), "scanner 0: 1357, scanner 2: 0");
+    }
+
+    #[test]
+    fn test_alignment() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        let alignment = solution.alignment();
+        assert_eq!(alignment.reference, 0);
+        assert_eq!(alignment.transforms.keys().copied().collect_vec(), [0, 1, 2, 3, 4]);
+        assert_eq!(alignment.position(2), Some((1105, -1205, 1229)));
+        assert_eq!(alignment.position(4), Some((-20, -1133, 1061)));
+
+        // The same beacon, as seen by scanners 0 and 1
+        assert_eq!(alignment.to_reference(1, (686, 422, 578)), Some((-618, -824, -621)));
+        assert_eq!(alignment.from_reference(1, (-618, -824, -621)), Some((686, 422, 578)));
+
+        // Scanners 1 and 4 see 12 beacons in common
+        let scanners = Scanners::try_from(EXAMPLE.to_string()).unwrap();
+        let seen_by_4 = scanners.0[&1]
+            .iter()
+            .map(|beacon| alignment.convert(1, 4, *beacon).unwrap())
+            .collect::<Scanner>();
+        assert_eq!(seen_by_4.intersection(&scanners.0[&4]).count(), 12);
+
+        for (id, transform) in &alignment.transforms {
+            let point = (17, -4, 1000);
+            assert_eq!(transform.inverse().apply(transform.apply(point)), point);
+            assert_eq!(alignment.convert(*id, *id, point), Some(point));
+        }
+        assert_eq!(alignment.to_reference(5, (0, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_rotations() {
+        let rotations = Rotation::all();
+        assert_eq!(rotations.len(), 24);
+        assert_eq!(rotations.iter().collect::<HashSet<_>>().len(), 24);
+        assert_eq!(rotations[0], Rotation::IDENTITY);
+        assert_eq!(Rotation::IDENTITY.matrix(), [[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+        for a in &rotations {
+            assert_eq!(a.determinant(), 1);
+            assert_eq!(a.compose(&a.inverse()), Rotation::IDENTITY);
+            assert_eq!(a.compose(&Rotation::IDENTITY), *a);
+            for b in &rotations {
+                assert!(rotations.contains(&a.compose(b)));
+                assert_eq!(a.compose(b).apply((1, 2, 3)), a.apply(b.apply((1, 2, 3))));
+            }
+        }
+
+        // Each orientation has a distinct facing direction and up direction
+        let orientations = rotations.iter().map(|rotation| (rotation.apply((1, 0, 0)), rotation.apply((0, 0, 1))));
+        assert_eq!(orientations.collect::<HashSet<_>>().len(), 24);
+    }
+
+    #[test]
+    fn test_components() {
+        let path = std::env::temp_dir().join("advent-day19-components.txt");
+        std::fs::write(&path, EXAMPLE.split("\n\n").take(3).join("\n\n")).unwrap();
+        let mut out = vec![];
+        components(&["--input".to_string(), path.display().to_string()], &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "scanner 0: scanners 0 1, 38 beacons, largest distance 1357\nscanner 2: scanners 2, 26 beacons, largest distance 0\n"
+        );
+        assert!(components(&["--input".to_string()], &mut vec![]).is_err());
+    }
+
+    #[test]
+    fn test_config() {
+        let ranged = AlignmentConfig {
+            sensor_range: Some(1000),
+            ..AlignmentConfig::default()
+        };
+        let mut solution = Solution::parse_with(EXAMPLE, &ranged).unwrap();
+        assert_eq!(solution.part1().to_string(), "79");
+
+        // Scanners 0 and 1 see exactly 12 beacons in common
+        let strict = AlignmentConfig {
+            min_overlap: 13,
+            ..AlignmentConfig::default()
+        };
+        let input = EXAMPLE.split("\n\n").take(2).join("\n\n");
+        assert_eq!(placement(&input, &strict).unplaced(), [1]);
+
+        // Scanner 1 reports a beacon at 0,-600,0 relative to scanner 0, which scanner 0 would have seen too
+        let noisy = input.replacen("--- scanner 1 ---\n", "--- scanner 1 ---\n68,646,-43\n", 1);
+        assert!(placement(&noisy, &AlignmentConfig::default()).is_complete());
+        assert_eq!(placement(&noisy, &ranged).unplaced(), [1]);
+    }
+
+    #[test]
+    fn test_example_part1() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "79");
+    }
+
+    #[test]
+    fn test_example_part2() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "3621");
+    }
+}
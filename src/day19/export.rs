@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use super::{Beacon, Component, ScannerId, Solution};
+use crate::Day;
+
+pub const USAGE: &str = "Usage: advent 19 export csv|xyz|ply [--input <file>|-]";
+
+/// The file formats of the point cloud
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudFormat {
+    /// A `kind,x,y,z,scanners` header, then a line per point
+    Csv,
+    /// A line of whitespace separated columns per point, after a `#` comment naming them
+    Xyz,
+    /// ASCII PLY, with the observing scanners as a list property and the scanners colored in red
+    Ply,
+}
+
+impl FromStr for CloudFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "csv" => Ok(CloudFormat::Csv),
+            "xyz" => Ok(CloudFormat::Xyz),
+            "ply" => Ok(CloudFormat::Ply),
+            _ => Err(format!("Invalid format: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointKind {
+    Beacon,
+    Scanner,
+}
+
+impl PointKind {
+    fn name(&self) -> &'static str {
+        match self {
+            PointKind::Beacon => "beacon",
+            PointKind::Scanner => "scanner",
+        }
+    }
+}
+
+/// A point of the cloud, in the frame of the reference scanner of its component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Point {
+    pub kind: PointKind,
+    pub position: Beacon,
+    /// The scanners that reported a beacon, or the scanner itself
+    pub scanners: Vec<ScannerId>,
+}
+
+/// The beacons of a component, each tagged with the scanners that reported it, then the scanners in id order
+pub fn points(component: &Component) -> Vec<Point> {
+    let mut beacons: BTreeMap<Beacon, Vec<ScannerId>> = BTreeMap::new();
+    for id in component.scanners() {
+        for beacon in &component.mapped.0[&id] {
+            beacons.entry(*beacon).or_default().push(id);
+        }
+    }
+
+    let beacons = beacons.into_iter().map(|(position, scanners)| Point {
+        kind: PointKind::Beacon,
+        position,
+        scanners,
+    });
+    let scanners = component.alignment.transforms.iter().map(|(id, transform)| Point {
+        kind: PointKind::Scanner,
+        position: transform.translation,
+        scanners: vec![*id],
+    });
+
+    beacons.chain(scanners).collect()
+}
+
+pub fn write(out: &mut dyn Write, format: CloudFormat, component: &Component) -> io::Result<()> {
+    let points = points(component);
+    match format {
+        CloudFormat::Csv => {
+            writeln!(out, "kind,x,y,z,scanners")?;
+            for point in &points {
+                let (x, y, z) = point.position;
+                writeln!(out, "{},{},{},{},{}", point.kind.name(), x, y, z, point.scanners.iter().join(" "))?;
+            }
+        }
+        CloudFormat::Xyz => {
+            writeln!(out, "# x y z kind scanners")?;
+            for point in &points {
+                let (x, y, z) = point.position;
+                writeln!(out, "{} {} {} {} {}", x, y, z, point.kind.name(), point.scanners.iter().join(","))?;
+            }
+        }
+        CloudFormat::Ply => {
+            writeln!(out, "ply\nformat ascii 1.0")?;
+            writeln!(out, "comment day 19 beacons and scanners, relative to scanner {}", component.reference)?;
+            writeln!(out, "element vertex {}", points.len())?;
+            for property in ["int x", "int y", "int z", "uchar red", "uchar green", "uchar blue"] {
+                writeln!(out, "property {}", property)?;
+            }
+            writeln!(out, "property list uchar int scanners\nend_header")?;
+            for point in &points {
+                let (x, y, z) = point.position;
+                let color = match point.kind {
+                    PointKind::Beacon => "255 255 255",
+                    PointKind::Scanner => "255 0 0",
+                };
+                writeln!(
+                    out,
+                    "{} {} {} {} {} {}",
+                    x,
+                    y,
+                    z,
+                    color,
+                    point.scanners.len(),
+                    point.scanners.iter().join(" ")
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `advent 19 export <format> [--input <file>|-]`: the beacons and scanners placed relative to scanner 0
+pub fn run(args: &[String], out: &mut dyn Write) -> Result<(), String> {
+    let (format, args) = args.split_first().ok_or(USAGE)?;
+    let format: CloudFormat = format.parse()?;
+    let input = super::tool_input(args, USAGE)?;
+    let mut solution = Solution::parse(&input).map_err(|err| format!("Invalid input: {}", err))?;
+    let placement = solution.solvable_placement().map_err(|err| format!("Invalid input: {}", err))?;
+
+    write(out, format, &placement.components[0])
+        .map_err(|err| format!("Could not write the point cloud: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{place, AlignmentConfig, Scanners};
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../../inputs/examples/day19.txt");
+
+    fn component() -> Component {
+        let scanners = Scanners::try_from(EXAMPLE.to_string()).unwrap();
+        place(&scanners, &AlignmentConfig::default()).components.remove(0)
+    }
+
+    fn written(format: CloudFormat) -> String {
+        let mut out = vec![];
+        write(&mut out, format, &component()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_points() {
+        let points = points(&component());
+        let beacons = points.iter().filter(|point| point.kind == PointKind::Beacon).collect_vec();
+        assert_eq!(beacons.len(), 79);
+        let shared = beacons.iter().find(|point| point.position == (-618, -824, -621)).unwrap();
+        assert_eq!(shared.scanners, [0, 1]);
+
+        let scanners = &points[79..];
+        assert_eq!(scanners.len(), 5);
+        assert_eq!((scanners[1].position, &scanners[1].scanners), ((68, -1246, -43), &vec![1]));
+    }
+
+    #[test]
+    fn test_write() {
+        let csv = written(CloudFormat::Csv);
+        assert!(csv.starts_with("kind,x,y,z,scanners\nbeacon,"));
+        assert!(csv.contains("\nbeacon,-618,-824,-621,0 1\n"));
+        assert!(csv.ends_with("\nscanner,-20,-1133,1061,4\n"));
+
+        let xyz = written(CloudFormat::Xyz);
+        assert!(xyz.contains("\n-618 -824 -621 beacon 0,1\n"));
+        assert_eq!(xyz.lines().count(), 1 + 79 + 5);
+
+        let ply = written(CloudFormat::Ply);
+        assert!(ply.starts_with("ply\nformat ascii 1.0\n"));
+        assert!(ply.contains("\nelement vertex 84\n"));
+        assert!(ply.contains("\nend_header\n"));
+        assert!(ply.contains("\n-618 -824 -621 255 255 255 2 0 1\n"));
+        assert!(ply.ends_with("\n-20 -1133 1061 255 0 0 1 4\n"));
+    }
+
+    #[test]
+    fn test_run() {
+        assert!(run(&["obj".to_string()], &mut vec![]).is_err());
+        assert!(run(&["csv".to_string(), "--input".to_string()], &mut vec![]).is_err());
+        assert_eq!("ply".parse(), Ok(CloudFormat::Ply));
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::write;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::{manhattan_distance, Alignment, Beacon, Coordinate, ScannerId, Transform, Vector, ROTATIONS};
+
+pub const USAGE: &str = "Usage: advent 19 generate <file> [--scanners <count>] [--structure random|chain|star|<parents>]\n                                 [--overlap <beacons>] [--beacons <beacons>] [--range <units>]\n                                 [--dropout <probability>] [--false-detections <count>] [--seed <seed>]";
+
+/// Which scanner each new scanner is placed next to, sharing `overlap` beacons with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Structure {
+    /// A random previous scanner
+    Random,
+    /// The previous scanner
+    Chain,
+    /// Scanner 0
+    Star,
+    /// The parent of each scanner from scanner 1 on, each parent being a previous scanner
+    Parents(Vec<ScannerId>),
+}
+
+impl FromStr for Structure {
+    type Err = String;
+
+    /// `random`, `chain`, `star`, or the comma separated parents of scanners 1, 2, ...
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "random" => Ok(Structure::Random),
+            "chain" => Ok(Structure::Chain),
+            "star" => Ok(Structure::Star),
+            _ => value
+                .split(',')
+                .map(|parent| parent.parse())
+                .collect::<Result<_, _>>()
+                .map(Structure::Parents)
+                .map_err(|_| format!("Invalid structure: {}", value)),
+        }
+    }
+}
+
+impl Structure {
+    fn parent(&self, rng: &mut impl Rng, id: ScannerId) -> ScannerId {
+        match self {
+            Structure::Random => rng.gen_range(0..id),
+            Structure::Chain => id - 1,
+            Structure::Star => 0,
+            Structure::Parents(parents) => parents[id - 1],
+        }
+    }
+}
+
+/// The shape of a synthetic dataset
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetConfig {
+    pub scanners: usize,
+    pub structure: Structure,
+    /// How far a scanner sees along each axis
+    pub range: Coordinate,
+    /// The beacons placed anywhere in the cube of each scanner
+    pub beacons_per_scanner: usize,
+    /// The beacons placed where the cube of each scanner meets the cube of its parent in the structure,
+    /// so that every scanner can be aligned with scanner 0 through the overlaps of the structure
+    pub overlap: usize,
+    /// The probability that a scanner misses a beacon in its range
+    pub dropout: f64,
+    /// The beacons each scanner reports where there are none
+    pub false_detections: usize,
+}
+
+impl Default for DatasetConfig {
+    /// Datasets like the puzzle inputs
+    fn default() -> Self {
+        Self {
+            scanners: 10,
+            structure: Structure::Random,
+            range: 1000,
+            beacons_per_scanner: 15,
+            overlap: 12,
+            dropout: 0.0,
+            false_detections: 0,
+        }
+    }
+}
+
+impl DatasetConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.scanners == 0 {
+            return Err("A dataset needs at least one scanner".to_string());
+        }
+        if self.range <= 0 {
+            return Err(format!("Invalid range: {}", self.range));
+        }
+        // Each scanner is at most the range away from its parent along each axis, and sees the range further,
+        // so the largest manhattan distance between two points is 6 times the range per scanner
+        if self.scanners as i64 * self.range as i64 * 6 > Coordinate::MAX as i64 {
+            return Err(format!("The range {} is too large for {} scanners", self.range, self.scanners));
+        }
+        if !(0.0..1.0).contains(&self.dropout) {
+            return Err(format!("Invalid dropout: {}", self.dropout));
+        }
+        if let Structure::Parents(parents) = &self.structure {
+            if parents.len() != self.scanners - 1 {
+                return Err(format!(
+                    "Expected the parents of {} scanners, got {}",
+                    self.scanners - 1,
+                    parents.len()
+                ));
+            }
+            if let Some((i, parent)) = parents.iter().enumerate().find(|(i, parent)| **parent > *i) {
+                return Err(format!("The parent of scanner {} must be a previous scanner, not {}", i + 1, parent));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What each scanner reports, with the actual beacons and scanners in the frame of scanner 0
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    /// The beacons each scanner reports, in its own frame
+    pub reports: BTreeMap<ScannerId, BTreeSet<Beacon>>,
+    /// The actual beacons reported by at least one scanner
+    pub beacons: BTreeSet<Beacon>,
+    pub truth: Alignment,
+}
+
+impl Dataset {
+    /// Places each scanner next to its parent in the structure, with its own orientation, scanner 0 being
+    /// the reference. The config must be valid
+    pub fn random(rng: &mut impl Rng, config: &DatasetConfig) -> Self {
+        let range = config.range;
+        let mut transforms = BTreeMap::from([(0, Transform::IDENTITY)]);
+        let mut world = BTreeSet::new();
+        let (low, high) = ((-range, -range, -range), (range, range, range));
+        place(rng, &mut world, config.beacons_per_scanner, low, high);
+        for id in 1..config.scanners {
+            let parent = transforms[&config.structure.parent(rng, id)].translation;
+            let position = (
+                parent.0 + rng.gen_range(-range..=range),
+                parent.1 + rng.gen_range(-range..=range),
+                parent.2 + rng.gen_range(-range..=range),
+            );
+            let rotation = *ROTATIONS.choose(rng).unwrap();
+            transforms.insert(id, Transform { rotation, translation: position });
+
+            let low = (position.0 - range, position.1 - range, position.2 - range);
+            let high = (position.0 + range, position.1 + range, position.2 + range);
+            place(rng, &mut world, config.beacons_per_scanner, low, high);
+            // The cubes meet since the scanners are less than twice the range apart along each axis
+            let shared_low = (
+                low.0.max(parent.0 - range),
+                low.1.max(parent.1 - range),
+                low.2.max(parent.2 - range),
+            );
+            let shared_high = (
+                high.0.min(parent.0 + range),
+                high.1.min(parent.1 + range),
+                high.2.min(parent.2 + range),
+            );
+            place(rng, &mut world, config.overlap, shared_low, shared_high);
+        }
+
+        let mut reports = BTreeMap::new();
+        let mut beacons = BTreeSet::new();
+        for (id, transform) in &transforms {
+            let to_scanner = transform.inverse();
+            let mut report = BTreeSet::new();
+            for beacon in &world {
+                let seen = to_scanner.apply(*beacon);
+                if [seen.0, seen.1, seen.2].iter().all(|c| c.abs() <= range) && !rng.gen_bool(config.dropout) {
+                    report.insert(seen);
+                    beacons.insert(*beacon);
+                }
+            }
+            for _ in 0..config.false_detections {
+                report.insert((
+                    rng.gen_range(-range..=range),
+                    rng.gen_range(-range..=range),
+                    rng.gen_range(-range..=range),
+                ));
+            }
+            reports.insert(*id, report);
+        }
+
+        Self {
+            reports,
+            beacons,
+            truth: Alignment {
+                reference: 0,
+                transforms,
+            },
+        }
+    }
+
+    /// The reports in the format of the puzzle input
+    pub fn input(&self) -> String {
+        self.reports
+            .iter()
+            .map(|(id, report)| {
+                let beacons = report.iter().map(|(x, y, z)| format!("{},{},{}\n", x, y, z)).join("");
+                format!("--- scanner {} ---\n{}", id, beacons)
+            })
+            .join("\n")
+    }
+
+    /// The expected answers as comments, then the position and row-major rotation matrix of each scanner
+    pub fn truth(&self) -> String {
+        let positions = self.truth.transforms.values().map(|transform| transform.translation).collect_vec();
+        let largest = itertools::iproduct!(positions.iter(), positions.iter())
+            .map(|(a, b)| manhattan_distance(a, b))
+            .max()
+            .unwrap_or(0);
+
+        let mut truth = format!(
+            "# beacons: {}\n# largest distance: {}\nscanner,x,y,z,rotation\n",
+            self.beacons.len(),
+            largest
+        );
+        for (id, Transform { rotation, translation: (x, y, z) }) in &self.truth.transforms {
+            let matrix = rotation.matrix().iter().flatten().join(" ");
+            truth.push_str(&format!("{},{},{},{},{}\n", id, x, y, z, matrix));
+        }
+        truth
+    }
+}
+
+/// Adds `count` new beacons between `low` and `high`
+fn place(rng: &mut impl Rng, world: &mut BTreeSet<Beacon>, count: usize, low: Vector, high: Vector) {
+    let target = world.len() + count;
+    while world.len() < target {
+        world.insert((
+            rng.gen_range(low.0..=high.0),
+            rng.gen_range(low.1..=high.1),
+            rng.gen_range(low.2..=high.2),
+        ));
+    }
+}
+
+/// The file holding the ground truth of a generated input: `big.txt` gives `big.truth.csv`
+pub fn truth_path(input: &Path) -> PathBuf {
+    input.with_extension("truth.csv")
+}
+
+/// `advent 19 generate <file> [options]`: writes a random input to `<file>` and its ground truth alongside
+pub fn run(args: &[String], out: &mut dyn Write) -> Result<(), String> {
+    let mut args = args.iter();
+    let path = PathBuf::from(args.next().ok_or(USAGE)?);
+    let mut config = DatasetConfig::default();
+    let mut seed = None;
+
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("Missing value for {}", arg))?;
+        let invalid = || format!("Invalid value for {}: {}", arg, value);
+        match arg.as_str() {
+            "--scanners" => config.scanners = value.parse().map_err(|_| invalid())?,
+            "--structure" => config.structure = value.parse()?,
+            "--overlap" => config.overlap = value.parse().map_err(|_| invalid())?,
+            "--beacons" => config.beacons_per_scanner = value.parse().map_err(|_| invalid())?,
+            "--range" => config.range = value.parse().map_err(|_| invalid())?,
+            "--dropout" => config.dropout = value.parse().map_err(|_| invalid())?,
+            "--false-detections" => config.false_detections = value.parse().map_err(|_| invalid())?,
+            "--seed" => seed = Some(value.parse().map_err(|_| invalid())?),
+            _ => return Err(format!("Unexpected argument: {}\n{}", arg, USAGE)),
+        }
+    }
+    config.validate()?;
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let dataset = Dataset::random(&mut rng, &config);
+
+    let truth = truth_path(&path);
+    write(&path, dataset.input()).map_err(|err| format!("Could not write {}: {}", path.display(), err))?;
+    write(&truth, dataset.truth()).map_err(|err| format!("Could not write {}: {}", truth.display(), err))?;
+    writeln!(
+        out,
+        "Wrote {} scanners and {} beacons to {}, and the ground truth to {}",
+        dataset.reports.len(),
+        dataset.beacons.len(),
+        path.display(),
+        truth.display()
+    )
+    .map_err(|err| format!("Could not write to stdout: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day19::{AlignmentConfig, Solution};
+    use crate::Day;
+
+    fn dataset(seed: u64, config: &DatasetConfig) -> Dataset {
+        Dataset::random(&mut StdRng::seed_from_u64(seed), config)
+    }
+
+    #[test]
+    fn test_alignment_matches_truth() {
+        let dataset = dataset(19, &DatasetConfig::default());
+        assert_eq!(dataset.reports.len(), 10);
+
+        let mut solution = Solution::parse(&dataset.input()).unwrap();
+        assert_eq!(solution.alignment(), &dataset.truth);
+        assert_eq!(solution.part1().to_string(), dataset.beacons.len().to_string());
+
+        // The puzzle range holds for a dataset without noise
+        let ranged = AlignmentConfig {
+            sensor_range: Some(1000),
+            ..AlignmentConfig::default()
+        };
+        let mut solution = Solution::parse_with(&dataset.input(), &ranged).unwrap();
+        assert_eq!(solution.alignment(), &dataset.truth);
+    }
+
+    #[test]
+    fn test_noise() {
+        let config = DatasetConfig {
+            false_detections: 5,
+            ..DatasetConfig::default()
+        };
+        let noisy = dataset(20, &config);
+        let mut solution = Solution::parse(&noisy.input()).unwrap();
+        assert_eq!(solution.alignment(), &noisy.truth);
+
+        let config = DatasetConfig {
+            dropout: 0.2,
+            ..DatasetConfig::default()
+        };
+        let reported = |dataset: Dataset| dataset.reports.values().map(BTreeSet::len).sum::<usize>();
+        assert!(reported(dataset(21, &config)) < reported(dataset(21, &DatasetConfig::default())));
+    }
+
+    #[test]
+    fn test_structure() {
+        let config = DatasetConfig {
+            scanners: 5,
+            structure: Structure::Parents(vec![0, 1, 1, 3]),
+            overlap: 20,
+            beacons_per_scanner: 5,
+            range: 500,
+            ..DatasetConfig::default()
+        };
+        let tree = dataset(22, &config);
+        assert_eq!(tree.reports.len(), 5);
+        let mut solution = Solution::parse_with(&tree.input(), &AlignmentConfig::default()).unwrap();
+        assert_eq!(solution.alignment(), &tree.truth);
+        assert_eq!(solution.part1().to_string(), tree.beacons.len().to_string());
+
+        // Each scanner sees at least the overlap with its parent
+        for (child, parent) in [(1, 0), (2, 1), (3, 1), (4, 3)] {
+            let seen_by_parent = tree.reports[&child]
+                .iter()
+                .map(|beacon| tree.truth.convert(child, parent, *beacon).unwrap())
+                .filter(|beacon| tree.reports[&parent].contains(beacon))
+                .count();
+            assert!(seen_by_parent >= 20);
+        }
+
+        for structure in [Structure::Chain, Structure::Star] {
+            let config = DatasetConfig {
+                structure,
+                ..DatasetConfig::default()
+            };
+            let dataset = dataset(23, &config);
+            assert_eq!(Solution::parse(&dataset.input()).unwrap().alignment(), &dataset.truth);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!("chain".parse(), Ok(Structure::Chain));
+        assert_eq!("0,0,1".parse(), Ok(Structure::Parents(vec![0, 0, 1])));
+        assert!("0,x".parse::<Structure>().is_err());
+
+        let config = |structure, scanners| DatasetConfig {
+            structure,
+            scanners,
+            ..DatasetConfig::default()
+        };
+        assert!(config(Structure::Parents(vec![0, 0, 1]), 4).validate().is_ok());
+        assert!(config(Structure::Parents(vec![0, 0]), 4).validate().is_err());
+        assert!(config(Structure::Parents(vec![0, 2, 1]), 4).validate().is_err());
+        assert!(config(Structure::Random, 0).validate().is_err());
+
+        let config = |range| DatasetConfig {
+            range,
+            ..DatasetConfig::default()
+        };
+        assert!(config(35_000_000).validate().is_ok());
+        assert!(config(36_000_000).validate().is_err());
+        assert!(config(Coordinate::MAX).validate().is_err());
+    }
+
+    #[test]
+    fn test_large_range() {
+        let config = DatasetConfig {
+            scanners: 5,
+            range: 1_000_000,
+            ..DatasetConfig::default()
+        };
+        config.validate().unwrap();
+        let dataset = dataset(24, &config);
+        let mut solution = Solution::parse(&dataset.input()).unwrap();
+        assert_eq!(solution.alignment(), &dataset.truth);
+        assert_eq!(solution.part1().to_string(), dataset.beacons.len().to_string());
+    }
+
+    #[test]
+    fn test_truth() {
+        let dataset = dataset(19, &DatasetConfig::default());
+        let truth = dataset.truth();
+        assert!(truth.starts_with(&format!("# beacons: {}\n", dataset.beacons.len())));
+        assert!(truth.contains("\nscanner,x,y,z,rotation\n0,0,0,0,1 0 0 0 1 0 0 0 1\n"));
+        assert_eq!(truth.lines().count(), 3 + 10);
+        assert_eq!(truth_path(Path::new("inputs/big.txt")), Path::new("inputs/big.truth.csv"));
+    }
+
+    #[test]
+    fn test_run() {
+        assert!(run(&[], &mut vec![]).is_err());
+        let args = ["big.txt", "--dropout", "1.5"].map(String::from);
+        assert!(run(&args, &mut vec![]).is_err());
+        let args = ["big.txt", "--scanners", "3", "--structure", "0"].map(String::from);
+        assert!(run(&args, &mut vec![]).is_err());
+        let args = ["big.txt", "--range", "0"].map(String::from);
+        assert!(run(&args, &mut vec![]).is_err());
+        let args = ["big.txt", "--seed"].map(String::from);
+        assert!(run(&args, &mut vec![]).is_err());
+    }
+}
@@ -1,47 +1,63 @@
 use std::collections::HashSet;
-use std::fs::read_to_string;
-use std::io::{self, Write};
 
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::{Answer, Day, ParseError, Result};
+
+const DAY: u8 = 20;
+
 pub struct Solution {
     lines: Vec<String>,
     algo: Vec<usize>,
     image: HashSet<(isize, isize)>,
     fill: isize,
+    steps: usize,
     current_min_x: isize,
     current_max_x: isize,
     current_min_y: isize,
     current_max_y: isize,
 }
 
-impl Solution {
-    pub fn init() -> Self {
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
         let mut lines = Vec::new();
-        for line in read_to_string("inputs/day20.txt").unwrap().lines() {
+        for line in input.lines() {
             lines.push(line.to_string());
         }
 
+        if lines.len() >= 2 && !lines[1].is_empty() {
+            return Err(ParseError::new(DAY, 2, 1, "expected an empty line after the algorithm"));
+        }
+        if lines.len() < 3 {
+            return Err(ParseError::new(DAY, lines.len() + 1, 1, "missing the input image"));
+        }
+
         let algo = lines[0]
             .chars()
-            .map(|c| match c {
-                '#' => 1,
-                '.' => 0,
-                _ => panic!("Invalid c in algo"),
+            .enumerate()
+            .map(|(x, c)| match c {
+                '#' => Ok(1),
+                '.' => Ok(0),
+                _ => Err(ParseError::new(DAY, 1, x + 1, format!("invalid character '{}' in the algorithm", c))),
             })
-            .collect_vec();
+            .collect::<Result<Vec<_>>>()?;
+        if algo.len() != 512 {
+            return Err(ParseError::new(DAY, 1, algo.len() + 1, format!("expected 512 characters in the algorithm, found {}", algo.len())));
+        }
 
         let mut image = HashSet::new();
         for (y, line) in lines[2..].iter().enumerate() {
             for (x, c) in line.chars().enumerate() {
-                if c == '#' {
-                    image.insert((x as isize, y as isize));
+                match c {
+                    '#' => { image.insert((x as isize, y as isize)); }
+                    '.' => (),
+                    _ => return Err(ParseError::new(DAY, y + 3, x + 1, format!("invalid character '{}' in the image", c))),
                 }
             }
         }
 
-        Self {
+        Ok(Self {
             algo,
             image,
             current_min_x: 0,
@@ -50,9 +66,28 @@ impl Solution {
             current_max_y: lines.len() as isize - 1 - 2,
             lines,
             fill: 0,
+            steps: 0,
+        })
+    }
+
+    fn part1(&mut self) -> Answer {
+        while self.steps < 2 {
+            self.enhance_image();
         }
+
+        Box::new(self.image.len())
     }
 
+    fn part2(&mut self) -> Answer {
+        while self.steps < 50 {
+            self.enhance_image();
+        }
+
+        Box::new(self.image.len())
+    }
+}
+
+impl Solution {
     fn out_of_image(&self, x: isize, y: isize) -> bool {
         x < self.current_min_x
             || x > self.current_max_x
@@ -95,6 +130,7 @@ impl Solution {
             self.fill = (self.fill + 1) % 2;
         }
         self.image = image;
+        self.steps += 1;
     }
 
     fn print_image(&self) {
@@ -109,40 +145,36 @@ impl Solution {
             println!();
         }
     }
+}
 
-    fn part1(&mut self) -> usize {
-        // 5765
-        self.enhance_image();
-        self.enhance_image();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        self.image.len()
-    }
+    const EXAMPLE: &str = include_str!("../inputs/examples/day20.txt");
 
-    fn part2(&mut self) -> usize {
-        // 18509
-        for _ in 0..48 {
-            self.enhance_image();
-        }
+    #[test]
+    fn test_parse_invalid() {
+        let err = Solution::parse("#.x\n\n#.").err().unwrap();
+        assert_eq!((err.day, err.line, err.column), (20, 1, 3));
+
+        let algo = ".".repeat(512);
+        let err = Solution::parse(&format!("{}\n\n#.\n.o", algo)).err().unwrap();
+        assert_eq!((err.line, err.column), (4, 2));
+
+        let err = Solution::parse(&algo).err().unwrap();
+        assert_eq!(err.line, 2);
+    }
 
-        self.image.len()
+    #[test]
+    fn test_example_part1() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "35");
     }
 
-    pub fn solve(&mut self) {
-        println!("========= DAY 20 ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
-
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
-
-        print!("Solving part 2: ");
-        io::stdout().flush().unwrap();
-        let start = std::time::Instant::now();
-        let part2 = self.part2();
-        let part2_time = start.elapsed();
-        println!("{:?} (took {:?})", part2, part2_time);
-        println!();
+    #[test]
+    fn test_example_part2() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "3351");
     }
 }
@@ -1,33 +1,47 @@
-use std::fs::read_to_string;
-use std::io::{self, Write};
-
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::{Answer, Day, ParseError, Result};
+
 use std::collections::HashMap;
 
+const DAY: u8 = 21;
+
 pub struct Solution {
-    lines: Vec<String>,
+    start: (usize, usize),
     cache: HashMap<(usize, usize, usize, usize), (usize, usize)>,
 }
 
-impl Solution {
-    pub fn init() -> Self {
-        let mut lines = Vec::new();
-        for line in read_to_string("inputs/day21.txt").unwrap().lines() {
-            lines.push(line.to_string());
-        }
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
+        // Each line reads "Player N starting position: P"
+        let positions = input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let error = |column: usize| ParseError::new(DAY, i + 1, column, format!("expected 'Player {} starting position: <1-10>'", i + 1));
+                let (_, position) = line.rsplit_once(": ").ok_or_else(|| error(1))?;
+                match position.parse() {
+                    Ok(position) if (1..=10).contains(&position) => Ok(position),
+                    _ => Err(error(line.len() - position.len() + 1)),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let Some((p1, p2)) = positions.into_iter().collect_tuple() else {
+            return Err(ParseError::new(DAY, 1, 1, "expected exactly two players"));
+        };
 
-        Self {
-            lines,
+        Ok(Self {
+            start: (p1, p2),
             cache: HashMap::new(),
-        }
+        })
     }
 
-    fn part1(&mut self) -> i32 {
+    fn part1(&mut self) -> Answer {
         let mut rolls = 0;
         let mut dice = (1..101).into_iter().cycle();
-        let mut players = vec![3, 4];
+        let mut players = vec![self.start.0, self.start.1];
         let mut scores = vec![0, 0];
         let mut current_player = 0;
 
@@ -45,9 +59,16 @@ impl Solution {
         }
 
         let loosing_score = scores.iter().min().unwrap();
-        *loosing_score * rolls
+        Box::new(*loosing_score * rolls)
+    }
+
+    fn part2(&mut self) -> Answer {
+        let (a, b) = self.game(self.start.0, self.start.1, 0, 0);
+        Box::new(a.max(b))
     }
+}
 
+impl Solution {
     // Want it to return the number of games won by player 1
     fn game(&mut self, p1: usize, p2: usize, s1: usize, s2: usize) -> (usize, usize) {
         if s2 >= 21 {
@@ -84,28 +105,38 @@ impl Solution {
         self.cache.insert((p1, p2, s1, s2), won_by_players);
         won_by_players
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../inputs/examples/day21.txt");
+
+    #[test]
+    fn test_parse() {
+        let solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.start, (4, 8));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = Solution::parse("Player 1 starting position: 4\nPlayer 2 starting position: 11").err().unwrap();
+        assert_eq!((err.day, err.line, err.column), (21, 2, 29));
+
+        let err = Solution::parse("Player 1 starting position: 4").err().unwrap();
+        assert_eq!(err.line, 1);
+    }
 
-    fn part2(&mut self) -> usize {
-        let (a, b) = self.game(3, 4, 0, 0);
-        a.max(b)
+    #[test]
+    fn test_example_part1() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "739785");
     }
 
-    pub fn solve(&mut self) {
-        println!("========= DAY 21 ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
-
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
-
-        print!("Solving part 2: ");
-        io::stdout().flush().unwrap();
-        let start = std::time::Instant::now();
-        let part2 = self.part2();
-        let part2_time = start.elapsed();
-        println!("{:?} (took {:?})", part2, part2_time);
-        println!();
+    #[test]
+    fn test_example_part2() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "444356092776315");
     }
 }
@@ -1,29 +1,29 @@
-use std::fs::read_to_string;
-use std::io::{self, Write};
-
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::{Answer, Day, ParseError, Result};
+
 pub struct Solution {
-    lines: Vec<String>,
+    cuboids: Vec<Cuboid>,
 }
 
-impl Solution {
-    pub fn init() -> Self {
-        let mut lines = Vec::new();
-        for line in read_to_string("inputs/day22.txt").unwrap().lines() {
-            lines.push(line.to_string());
-        }
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
+        let cuboids = input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| Cuboid::from_line(line).map_err(|err| err.on_line(i + 1)))
+            .collect::<Result<_>>()?;
 
-        Self { lines }
+        Ok(Self { cuboids })
     }
 
-    fn part1(&mut self) -> T{
+    fn part1(&mut self) -> Answer {
         let cuboids = self
-            .lines
+            .cuboids
             .iter()
-            .map(|line| Cuboid::from_line(line))
             .filter(|cuboid| cuboid.valid_for_part_1())
+            .cloned()
             .collect_vec();
         let mut cores: Vec<Cuboid> = vec![];
 
@@ -38,15 +38,11 @@ impl Solution {
             cores.extend(to_add);
         }
 
-        cores.iter().fold(0, |acc, cuboid| acc + cuboid.signed_volume())
+        Box::new(cores.iter().fold(0, |acc, cuboid| acc + cuboid.signed_volume()))
     }
 
-    fn part2(&mut self) -> T {
-        let cuboids = self
-            .lines
-            .iter()
-            .map(|line| Cuboid::from_line(line))
-            .collect_vec();
+    fn part2(&mut self) -> Answer {
+        let cuboids = self.cuboids.clone();
         let mut cores: Vec<Cuboid> = vec![];
 
         for cuboid in cuboids {
@@ -60,31 +56,14 @@ impl Solution {
             cores.extend(to_add);
         }
 
-        cores.iter().fold(0, |acc, cuboid| acc + cuboid.signed_volume())
-    }
-
-    pub fn solve(&mut self) {
-        println!("========= DAY 22 ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
-
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
-
-        print!("Solving part 2: ");
-        io::stdout().flush().unwrap();
-        let start = std::time::Instant::now();
-        let part2 = self.part2();
-        let part2_time = start.elapsed();
-        println!("{:?} (took {:?})", part2, part2_time);
-        println!();
+        Box::new(cores.iter().fold(0, |acc, cuboid| acc + cuboid.signed_volume()))
     }
 }
 
 type T = i64;
 
+const DAY: u8 = 22;
+
 #[derive(Debug, Clone)]
 struct Cuboid {
     added: bool,
@@ -128,24 +107,46 @@ impl Cuboid {
         self.min_x >= -50 && self.max_x <= 50 && self.min_y >= -50 && self.max_y <= 50 && self.min_z >= -50 && self.max_z <= 50
     }
 
-    pub fn from_line(line: &str) -> Self {
-        let (state_str, remainder) = line.split_once(" ").unwrap();
+    // Errors are reported on line 1, the caller knows the actual line number
+    pub fn from_line(line: &str) -> Result<Self> {
+        let error = |column: usize, message: String| ParseError::new(DAY, 1, column, message);
+
+        let (state_str, remainder) = line
+            .split_once(" ")
+            .ok_or_else(|| error(1, "expected 'on' or 'off' followed by the ranges".to_string()))?;
         let added = match state_str {
             "on" => true,
             "off" => false,
-            _ => panic!("Invalid state"),
+            _ => return Err(error(1, format!("invalid state '{}'", state_str))),
         };
 
         let mut values = Vec::new();
-        for part in remainder.split(",") {
-            let (_axis, range) = part.split_once("=").unwrap();
-            let (min, max) = range.split_once("..").unwrap();
-            values.push(min.parse().unwrap());
-            values.push(max.parse().unwrap());
+        let mut column = state_str.len() + 2;
+        for pair in remainder.split(",").zip_longest(["x", "y", "z"]) {
+            let (part, expected_axis) = match pair.both() {
+                Some(both) => both,
+                None => return Err(error(column, "expected exactly three ranges, for x, y and z".to_string())),
+            };
+
+            let (axis, range) = part
+                .split_once("=")
+                .ok_or_else(|| error(column, format!("expected '{}=<min>..<max>'", expected_axis)))?;
+            if axis != expected_axis {
+                return Err(error(column, format!("expected axis '{}', found '{}'", expected_axis, axis)));
+            }
+
+            let range_column = column + axis.len() + 1;
+            let (min, max) = range
+                .split_once("..")
+                .ok_or_else(|| error(range_column, format!("expected a '<min>..<max>' range, found '{}'", range)))?;
+            values.push(min.parse().map_err(|_| error(range_column, format!("invalid bound '{}'", min)))?);
+            values.push(max.parse().map_err(|_| error(range_column + min.len() + 2, format!("invalid bound '{}'", max)))?);
+
+            column += part.len() + 1;
         }
 
         let (min_x, max_x, min_y, max_y, min_z, max_z) = values.into_iter().collect_tuple().unwrap();
-        Self::new(min_x, max_x, min_y, max_y, min_z, max_z, added)
+        Ok(Self::new(min_x, max_x, min_y, max_y, min_z, max_z, added))
     }
 
     pub fn signed_volume(&self) -> T {
@@ -165,7 +166,7 @@ mod tests {
     #[test]
     fn test_parse() {
         let line = "on x=-7..46,y=-33..20,z=-18..35";
-        let cuboid = Cuboid::from_line(line);
+        let cuboid = Cuboid::from_line(line).unwrap();
         assert_eq!(cuboid.min_x, -7);
         assert_eq!(cuboid.max_x, 46);
         assert_eq!(cuboid.min_y, -33);
@@ -174,4 +175,43 @@ mod tests {
         assert_eq!(cuboid.max_z, 35);
         assert_eq!(cuboid.added, true);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = Cuboid::from_line("of x=1..2,y=1..2,z=1..2").unwrap_err();
+        assert_eq!((err.day, err.column), (22, 1));
+
+        let err = Cuboid::from_line("on x=1..2,y=1..a,z=1..2").unwrap_err();
+        assert_eq!(err.column, 16);
+
+        let err = Cuboid::from_line("on x=1..2,z=1..2,y=1..2").unwrap_err();
+        assert_eq!(err.column, 11);
+
+        let err = Cuboid::from_line("on x=1..2,y=1..2").unwrap_err();
+        assert_eq!(err.column, 18);
+
+        let err = Solution::parse("on x=1..2,y=1..2,z=1..2\non x=1..2,y=1-2,z=1..2").err().unwrap();
+        assert_eq!((err.line, err.column), (2, 13));
+    }
+
+    const EXAMPLE: &str = include_str!("../inputs/examples/day22.txt");
+    const REBOOT_EXAMPLE: &str = include_str!("../inputs/examples/day22_reboot.txt");
+
+    #[test]
+    fn test_example_part1() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "39");
+
+        let mut solution = Solution::parse(REBOOT_EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "474140");
+    }
+
+    #[test]
+    fn test_example_part2() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "39");
+
+        let mut solution = Solution::parse(REBOOT_EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "2758514936282235");
+    }
+}
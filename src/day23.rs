@@ -1,38 +1,43 @@
 use std::collections::{BinaryHeap, HashMap, HashSet};
-use std::fs::read_to_string;
 use std::hash::Hash;
-use std::io::{self, Write};
 
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::{Answer, Day, ParseError, Result};
+
+const DAY: u8 = 23;
+
 pub struct Solution {
     lines: Vec<String>,
+    map: Map,
 }
 
-impl Solution {
-    pub fn init() -> Self {
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
         let mut lines = Vec::new();
-        for line in read_to_string("inputs/day23.txt").unwrap().lines() {
+        for line in input.lines() {
             lines.push(line.to_string());
         }
 
-        Self { lines }
+        let map = Map::parse(&lines)?;
+        Ok(Self { lines, map })
     }
 
-    fn part1(&mut self) -> Cost {
-        let initial_map = Map::parse(&self.lines);
-        Self::astar(initial_map)
+    fn part1(&mut self) -> Answer {
+        Box::new(Self::astar(self.map.clone()))
     }
 
-    fn part2(&mut self) -> Cost{
+    fn part2(&mut self) -> Answer {
         let mut updated_lines = self.lines.clone();
         updated_lines.insert(3, "  #D#C#B#A#".to_string());
         updated_lines.insert(4, "  #D#B#A#C#".to_string());
-        let initial_map = Map::parse(&updated_lines);
-        Self::astar(initial_map)
+        let initial_map = Map::parse(&updated_lines).expect("The unfolded burrow is valid since the folded one is");
+        Box::new(Self::astar(initial_map))
     }
+}
 
+impl Solution {
     fn astar(initial_map: Map) -> Cost {
         // Implement A* algorithm
         let mut open_set = BinaryHeap::new();
@@ -72,25 +77,6 @@ impl Solution {
 
         -1
     }
-
-    pub fn solve(&mut self) {
-        println!("========= DAY 23 ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
-
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
-
-        print!("Solving part 2: ");
-        io::stdout().flush().unwrap();
-        let start = std::time::Instant::now();
-        let part2 = self.part2();
-        let part2_time = start.elapsed();
-        println!("{:?} (took {:?})", part2, part2_time);
-        println!();
-    }
 }
 
 type Cost = i32;
@@ -138,23 +124,24 @@ struct Entity {
 }
 
 impl Entity {
-    pub fn parse(x: usize, y: usize, entity_type: char, max_y: usize) -> Option<Self> {
+    pub fn parse(x: usize, y: usize, entity_type: char, max_y: usize) -> Result<Option<Self>> {
         let (target_x, move_cost) = match entity_type {
             'A' => (3, 1),
             'B' => (5, 10),
             'C' => (7, 100),
             'D' => (9, 1000),
-            _ => return None,
+            '#' | '.' | ' ' => return Ok(None),
+            _ => return Err(ParseError::new(DAY, y + 1, x + 1, format!("invalid character '{}' in the burrow", entity_type))),
         };
 
-        Some(Self {
+        Ok(Some(Self {
             x,
             y,
             max_y,
             target_x,
             move_cost,
             entity_type,
-        })
+        }))
     }
 
     pub fn in_theorical_final_spot(&self) -> bool {
@@ -280,20 +267,22 @@ pub fn manathan_distance(point_a: (usize, usize), point_b: (usize, usize)) -> Co
 }
 
 impl Map {
-    pub fn parse(lines: &Vec<String>) -> Self {
+    pub fn parse(lines: &Vec<String>) -> Result<Self> {
+        if lines.len() < 4 {
+            return Err(ParseError::new(DAY, lines.len() + 1, 1, "expected the hallway, the side rooms and the bottom wall"));
+        }
+
         let max_y = lines.len() - 2;
-        let entities = lines.iter().enumerate().map(|(y, line)| {
-            line.chars().enumerate().map(move |(x, c)| {
-                Entity::parse(x, y, c, max_y)
-            })
-        })
-            .flatten()
-            .filter_map(|e| e)
-            .collect::<Vec<_>>();
-        
-        Self {
-            entities,
+        let mut entities = vec![];
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                entities.extend(Entity::parse(x, y, c, max_y)?);
+            }
         }
+
+        Ok(Self {
+            entities,
+        })
     }
 
     pub fn heuristic(&self) -> Cost {
@@ -401,4 +390,32 @@ impl PartialOrd for Node {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = Solution::parse("#############\n#...........#\n###B#C#B#D###\n  #A#E#C#A#\n  #########").err().unwrap();
+        assert_eq!((err.day, err.line, err.column), (23, 4, 6));
+
+        let err = Solution::parse("#############\n#...........#").err().unwrap();
+        assert_eq!(err.line, 3);
+    }
+
+    const EXAMPLE: &str = include_str!("../inputs/examples/day23.txt");
+
+    #[test]
+    fn test_example_part1() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "12521");
+    }
+
+    #[test]
+    fn test_example_part2() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "44169");
+    }
+}
@@ -1,66 +1,98 @@
 use std::collections::HashSet;
-use std::fs::read_to_string;
-use std::io::{self, Write};
 
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::{Answer, Day, ParseError, Result};
+
 pub struct Solution {
-    lines: Vec<String>,
+    blocks: Vec<Block>,
 }
 
-impl Solution {
-    pub fn init() -> Self {
-        let mut lines = Vec::new();
-        for line in read_to_string("inputs/day24.txt").unwrap().lines() {
-            lines.push(line.to_string());
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
+        let blocks: Vec<_> = input
+            .lines()
+            .chunks(BLOCK_LENGTH)
+            .into_iter()
+            .enumerate()
+            .map(|(i, block)| Block::parse(&block.collect_vec(), i * BLOCK_LENGTH + 1))
+            .collect::<Result<_>>()?;
+        if blocks.is_empty() {
+            return Err(ParseError::new(DAY, 1, 1, "empty input"));
         }
 
-        Self { lines }
+        Ok(Self { blocks })
     }
 
-    fn part1(&mut self) -> Value {
+    fn part1(&mut self) -> Answer {
         let digits = [9, 8, 7, 6, 5, 4, 3, 2, 1];
         let mut invalid_states = HashSet::new();
-        generate_model_number(&digits, 0, 0, 0, &mut invalid_states).unwrap()
+        Box::new(generate_model_number(&self.blocks, &digits, 0, 0, 0, &mut invalid_states).unwrap())
     }
 
-    fn part2(&mut self) -> Value {
+    fn part2(&mut self) -> Answer {
         let digits = [1, 2, 3, 4, 5, 6, 7, 8, 9];
         let mut invalid_states = HashSet::new();
-        generate_model_number(&digits, 0, 0, 0, &mut invalid_states).unwrap()
-    }
-
-    pub fn solve(&mut self) {
-        println!("========= DAY 24 ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
-
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
-
-        print!("Solving part 2: ");
-        io::stdout().flush().unwrap();
-        let start = std::time::Instant::now();
-        let part2 = self.part2();
-        let part2_time = start.elapsed();
-        println!("{:?} (took {:?})", part2, part2_time);
-        println!();
+        Box::new(generate_model_number(&self.blocks, &digits, 0, 0, 0, &mut invalid_states).unwrap())
     }
 }
 
 type Value = i128;
 
-fn generate_model_number(digits_to_try: &[Value; 9], mut model_number: Value, original_z: Value, depth: usize, invalid_states: &mut HashSet<(Value, usize)>) -> Option<Value> {
-    if invalid_states.contains(&(original_z, depth)) || depth == 14 {
+const DAY: u8 = 24;
+
+// The MONAD program checks each digit with the same 18 instructions, only three operands change
+const BLOCK_LENGTH: usize = 18;
+const BLOCK: [&str; BLOCK_LENGTH] = [
+    "inp w", "mul x 0", "add x z", "mod x 26", "div z ", "add x ", "eql x w", "eql x 0", "mul y 0",
+    "add y 25", "mul y x", "add y 1", "mul z y", "mul y 0", "add y w", "add y ", "mul y x", "add z y",
+];
+const DIV_INDEX: usize = 4;
+const ADD_INDEX: usize = 5;
+const SECOND_ADD_INDEX: usize = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Block {
+    div: Value,        // div z <div>
+    add: Value,        // add x <add>
+    second_add: Value, // add y <second_add>, right before the last `mul y x`
+}
+
+impl Block {
+    fn parse(instructions: &[&str], first_line: usize) -> Result<Self> {
+        if instructions.len() != BLOCK_LENGTH {
+            return Err(ParseError::new(DAY, first_line + instructions.len(), 1, format!("expected {} instructions per digit", BLOCK_LENGTH)));
+        }
+
+        let mut operands = vec![];
+        for (i, (instruction, expected)) in instructions.iter().zip(BLOCK).enumerate() {
+            let error = |column: usize| ParseError::new(DAY, first_line + i, column, format!("expected '{}', found '{}'", expected, instruction));
+            if ![DIV_INDEX, ADD_INDEX, SECOND_ADD_INDEX].contains(&i) {
+                if *instruction != expected {
+                    return Err(error(1));
+                }
+                continue;
+            }
+
+            let operand = instruction.strip_prefix(expected).ok_or_else(|| error(1))?;
+            operands.push(operand.parse().map_err(|_| error(expected.len() + 1))?);
+        }
+
+        Ok(Self {
+            div: operands[0],
+            add: operands[1],
+            second_add: operands[2],
+        })
+    }
+}
+
+fn generate_model_number(blocks: &[Block], digits_to_try: &[Value; 9], mut model_number: Value, original_z: Value, depth: usize, invalid_states: &mut HashSet<(Value, usize)>) -> Option<Value> {
+    if invalid_states.contains(&(original_z, depth)) || depth == blocks.len() {
         return None;
     }
 
-    let div_values = [1, 1, 1, 26, 26, 1, 26, 26, 1, 26, 1, 1, 26, 26];
-    let add_values = [12, 14, 11, -9, -7, 11, -1, -16, 11, -15, 10, 12, -4, 0];
-    let second_add_values = [15, 12, 15, 12, 15, 2, 11, 15, 10, 2, 0, 0, 15, 15];
+    let block = blocks[depth];
 
     model_number *= 10;
 
@@ -71,25 +103,25 @@ fn generate_model_number(digits_to_try: &[Value; 9], mut model_number: Value, or
         let mut z = original_z;
 
         x %= 26;
-        z /= div_values[depth];
-        x += add_values[depth];
+        z /= block.div;
+        x += block.add;
         x = if x == w { 1 } else { 0 };
         x = if x == 0 { 1 } else { 0 };
         y *= x;
         y += 1;
         z *= y;
         y = w;
-        y += second_add_values[depth];
+        y += block.second_add;
         y *= x;
         z += y;
 
         // We are at the end of the number and we have a solution, return it
-        if z == 0 && depth == 13 {
+        if z == 0 && depth == blocks.len() - 1 {
             return Some(model_number + w);
         }
 
         // Check the next depth
-        if let Some(result) = generate_model_number(digits_to_try, model_number + w, z, depth + 1, invalid_states) {
+        if let Some(result) = generate_model_number(blocks, digits_to_try, model_number + w, z, depth + 1, invalid_states) {
             return Some(result);
         }
     }
@@ -98,3 +130,36 @@ fn generate_model_number(digits_to_try: &[Value; 9], mut model_number: Value, or
     invalid_states.insert((original_z, depth));
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block() {
+        let input = "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z 26\nadd x -9\neql x w\neql x 0\nmul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\nadd y 12\nmul y x\nadd z y\n";
+        let solution = Solution::parse(input).unwrap();
+        assert_eq!(solution.blocks, vec![Block { div: 26, add: -9, second_add: 12 }]);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = Solution::parse("inp w\nmul x 0\nadd x z\nmod x 26\ndiv z 26\nadd x -9\neql x w\neql x 0\nmul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\nadd y a\nmul y x\nadd z y\n").err().unwrap();
+        assert_eq!((err.day, err.line, err.column), (24, 16, 7));
+
+        let err = Solution::parse("inp w\nmul x 0\nadd x y").err().unwrap();
+        assert_eq!(err.line, 4);
+
+        let err = Solution::parse("").err().unwrap();
+        assert_eq!((err.line, err.column, err.message.as_str()), (1, 1, "empty input"));
+    }
+
+    #[test]
+    fn test_single_digit() {
+        // x ends up at 8, so z is only left at 0 when the digit is 8
+        let input = "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z 26\nadd x 8\neql x w\neql x 0\nmul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\nadd y 1\nmul y x\nadd z y\n";
+        let mut solution = Solution::parse(input).unwrap();
+        assert_eq!(solution.part1().to_string(), "8");
+        assert_eq!(solution.part2().to_string(), "8");
+    }
+}
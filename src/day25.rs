@@ -2,25 +2,25 @@
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
-use std::fs::read_to_string;
-use std::io::{self, Write};
 
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::{Answer, Day, ParseError, Result};
+
 pub struct Solution {
-    content: String,
+    map: Map,
 }
 
-impl Solution {
-    pub fn init() -> Self {
-        let content = read_to_string("inputs/day25.txt").unwrap();
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
+        let map = Map::try_from(input)?;
 
-        Self { content }
+        Ok(Self { map })
     }
 
-    fn part1(&mut self) -> usize{
-        let mut map = Map::try_from(self.content.as_str()).unwrap();
+    fn part1(&mut self) -> Answer {
+        let mut map = self.map.clone();
         let mut seen = HashSet::new();
         let mut step = 0;
 
@@ -35,28 +35,19 @@ impl Solution {
             step += 1;
         }
 
-        step
+        Box::new(step)
     }
 
-    pub fn solve(&mut self) {
-        println!("========= DAY 25 ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
-
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
-
-        print!("Solving part 2: ");
-        println!("MERRY CHRISTMAS");
-        println!();
+    fn part2(&mut self) -> Answer {
+        Box::new("MERRY CHRISTMAS")
     }
 }
 
 
 type Coordinate = i32;
 
+const DAY: u8 = 25;
+
 #[derive(Debug, PartialEq, Clone)]
 enum Creature {
     EastFacing,
@@ -72,6 +63,7 @@ impl Creature {
     }
 }
 
+#[derive(Clone)]
 struct Map {
     width: Coordinate,
     height: Coordinate,
@@ -80,22 +72,27 @@ struct Map {
 }
 
 impl TryFrom<&str> for Map {
-    type Error = Box<dyn std::error::Error>;
+    type Error = ParseError;
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
         let lines = value.lines().collect::<Vec<_>>();
-        let width = lines[0].len() as Coordinate;
+        let width = lines.first().ok_or_else(|| ParseError::new(DAY, 1, 1, "empty map"))?.len() as Coordinate;
         let height = lines.len() as Coordinate;
 
         let mut creatures = HashMap::new();
         for (y, line) in lines.iter().enumerate() {
+            if line.len() as Coordinate != width {
+                let column = line.len().min(width as usize) + 1;
+                return Err(ParseError::new(DAY, y + 1, column, format!("expected {} columns, found {}", width, line.len())));
+            }
+
             for (x, c) in line.chars().enumerate() {
                 let coord = (x as Coordinate, y as Coordinate);
                 match c {
                     '>' => { creatures.insert(coord, Creature::EastFacing); }
                     'v' => { creatures.insert(coord, Creature::SouthFacing); }
                     '.' => (), // empty space
-                    _ => return Err(format!("Invalid character: {}", c).into()),
+                    _ => return Err(ParseError::new(DAY, y + 1, x + 1, format!("invalid character '{}'", c))),
                 };
             }
         }
@@ -203,4 +200,29 @@ mod tests {
         (0..steps).for_each(|_| map.tick());
         assert_eq!(map.to_string(), expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = Map::try_from("...\n.>.\n..x\n").err().unwrap();
+        assert_eq!((err.day, err.line, err.column), (25, 3, 3));
+
+        let err = Map::try_from("...\n.>\n").err().unwrap();
+        assert_eq!((err.line, err.column), (2, 3));
+
+        assert!(Map::try_from("").is_err());
+    }
+
+    const EXAMPLE: &str = include_str!("../inputs/examples/day25.txt");
+
+    #[test]
+    fn test_example_part1() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "58");
+    }
+
+    #[test]
+    fn test_example_part2() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "MERRY CHRISTMAS");
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt::Display;
+
+/// An invalid puzzle input, pointing at the offending character.
+/// Lines and columns start at 1, like in an editor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+impl ParseError {
+    pub fn new(day: u8, line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Moves an error found while parsing a single line to the actual line number in the input
+    pub fn on_line(self, line: usize) -> Self {
+        Self { line, ..self }
+    }
+
+    /// Shifts the column of an error found while parsing a slice starting at `offset` in its line
+    pub fn shifted(self, offset: usize) -> Self {
+        Self {
+            column: self.column + offset,
+            ..self
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: {}",
+            self.day, self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
+use std::fmt::Display;
+use std::io::{self, Write};
+
 pub mod day18;
 pub mod day19;
 pub mod day20;
@@ -7,4 +10,82 @@ pub mod day21;
 pub mod day22;
 pub mod day23;
 pub mod day24;
-pub mod day25;
\ No newline at end of file
+pub mod day25;
+
+pub mod answers;
+pub mod bench;
+pub mod error;
+pub mod report;
+pub mod runner;
+pub mod scaffold;
+
+pub use error::{ParseError, Result};
+
+/// The answer of a part, anything that can be printed
+pub type Answer = Box<dyn Display + Send>;
+
+/// A puzzle solution: `parse` builds the state from the puzzle input, then each part computes its answer from it.
+/// Solutions are `Send` so that the runner can solve each part on its own thread
+pub trait Day: Send {
+    fn parse(input: &str) -> Result<Self>
+    where
+        Self: Sized;
+
+    fn part1(&mut self) -> Answer;
+
+    fn part2(&mut self) -> Answer;
+
+    /// Writes the intermediate steps of the solution, for `--trace`.
+    /// Returns false for the days that have nothing to show
+    fn trace(&mut self, _out: &mut dyn Write) -> io::Result<bool> {
+        Ok(false)
+    }
+}
+
+/// Builds a solution for a given day, type-erased so that all days fit in the registry
+pub type Loader = fn(&str) -> Result<Box<dyn Day>>;
+
+fn load<D: Day + 'static>(input: &str) -> Result<Box<dyn Day>> {
+    Ok(Box::new(D::parse(input)?))
+}
+
+/// All the implemented days, in order
+pub const REGISTRY: &[(u8, Loader)] = &[
+    (18, load::<day18::Solution>),
+    (19, load::<day19::Solution>),
+    (20, load::<day20::Solution>),
+    (21, load::<day21::Solution>),
+    (22, load::<day22::Solution>),
+    (23, load::<day23::Solution>),
+    (24, load::<day24::Solution>),
+    (25, load::<day25::Solution>),
+];
+
+/// A day-specific command, run as `advent <day> <name> <args>`, writing its result to `out`
+pub type Tool = fn(&[String], &mut dyn Write) -> std::result::Result<(), String>;
+
+/// The commands of each day, by name
+pub const TOOLS: &[(u8, &str, Tool)] = &[
+    (18, "inspect", day18::explore::run),
+    (19, "components", day19::components),
+    (19, "export", day19::export::run),
+    (19, "generate", day19::synthetic::run),
+];
+
+pub fn days() -> impl Iterator<Item = u8> {
+    REGISTRY.iter().map(|(day, _)| *day)
+}
+
+pub fn loader(day: u8) -> Option<Loader> {
+    REGISTRY
+        .iter()
+        .find(|(registered, _)| *registered == day)
+        .map(|(_, loader)| *loader)
+}
+
+pub fn tool(day: u8, name: &str) -> Option<Tool> {
+    TOOLS
+        .iter()
+        .find(|(registered, registered_name, _)| *registered == day && *registered_name == name)
+        .map(|(_, _, tool)| *tool)
+}
@@ -1,16 +1,130 @@
-#![allow(dead_code)]
-#![allow(unused_imports)]
-
-use advent::day18;
-use advent::day19;
-use advent::day20;
-use advent::day21;
-use advent::day22;
-use advent::day23;
-use advent::day24;
-use advent::day25;
+use std::io::{self, Write};
+use std::process::exit;
+use std::slice;
+
+use advent::bench::BenchHistory;
+use advent::report::{self, Format};
+use advent::runner::{self, Options, USAGE};
+use advent::{loader, tool, Loader};
 
 fn main() {
-    day25::Solution::init().solve();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let [day, name, tool_args @ ..] = args.as_slice() {
+        if let Some(tool) = day.parse().ok().and_then(|day| tool(day, name)) {
+            if let Err(err) = tool(tool_args, &mut io::stdout()) {
+                eprintln!("{}", err);
+                exit(2);
+            }
+            return;
+        }
+    }
+
+    let options = match Options::from_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            exit(2);
+        }
+    };
+
+    let Some(day) = options.day else {
+        eprintln!("Missing day\n{}", USAGE);
+        exit(2);
+    };
+
+    let Some(loader) = loader(day) else {
+        eprintln!("Day {} is not implemented", day);
+        exit(1);
+    };
+
+    let answers = match options.load_answers() {
+        Ok(answers) => answers,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    };
+
+    if options.bench.is_some() {
+        if let Err(err) = bench(day, loader, &options) {
+            eprintln!("{}", err);
+            exit(1);
+        }
+        return;
+    }
+
+    if options.trace {
+        if let Err(err) = trace(day, loader, &options) {
+            eprintln!("{}", err);
+            exit(1);
+        }
+        return;
+    }
+
+    // The text is written as each part is solved, the other formats once all of them are done
+    let mut out: Box<dyn Write> = match options.format {
+        Format::Text => Box::new(io::stdout()),
+        _ => Box::new(io::sink()),
+    };
+    let report = runner::run_day(
+        out.as_mut(),
+        day,
+        loader,
+        &options.input,
+        &options.parts,
+        answers.as_ref(),
+        options.timeout,
+    )
+    .expect("Could not write to stdout");
+
+    match options.format {
+        Format::Text => {
+            if let Some(err) = &report.error {
+                eprintln!("{}", err);
+            }
+        }
+        format => {
+            let records = report::records(slice::from_ref(&report), &options.parts);
+            report::write(&mut io::stdout(), format, &records).expect("Could not write to stdout");
+        }
+    }
+
+    if report.failed() {
+        exit(1);
+    }
+}
+
+fn bench(day: u8, loader: Loader, options: &Options) -> Result<(), String> {
+    let input = options
+        .input
+        .read(day)
+        .map_err(|err| format!("Could not read the input of day {}: {}", day, err))?;
+    let baseline = options.load_baseline()?;
+    let stats = runner::bench(&mut io::stdout(), day, loader, &input, options, baseline.as_ref())
+        .expect("Could not write to stdout")
+        .map_err(|err| format!("Invalid input: {}", err))?;
+
+    if let Some(path) = &options.save {
+        let mut history = BenchHistory::load_or_default(path)?;
+        for (step, step_stats) in stats {
+            history.insert(day, step, step_stats);
+        }
+        history.save(path)?;
+    }
+
+    Ok(())
 }
 
+fn trace(day: u8, loader: Loader, options: &Options) -> Result<(), String> {
+    let input = options
+        .input
+        .read(day)
+        .map_err(|err| format!("Could not read the input of day {}: {}", day, err))?;
+    let mut solution = loader(&input).map_err(|err| format!("Invalid input: {}", err))?;
+
+    match solution.trace(&mut io::stdout()) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("Day {} has no trace", day)),
+        Err(err) => Err(format!("Could not write to stdout: {}", err)),
+    }
+}
@@ -0,0 +1,267 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::answers::Status;
+use crate::runner::{DayReport, Failure, Part};
+
+/// How the results of a run are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Invalid format: {}", value)),
+        }
+    }
+}
+
+/// The outcome of a single part, flattened for scripts.
+/// A day whose input could not be read or parsed gives an error record for each requested part,
+/// and a part that timed out or panicked has its failure as error message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub day: u8,
+    pub part: Part,
+    pub answer: Option<String>,
+    pub duration_ns: Option<u128>,
+    pub error: Option<String>,
+    pub failure: Option<Failure>,
+    pub check: Option<Status>,
+}
+
+impl Record {
+    pub fn status(&self) -> &'static str {
+        match (&self.failure, &self.error) {
+            (Some(Failure::Timeout(_)), _) => "timeout",
+            (Some(Failure::Panic(_)), _) => "panic",
+            (None, Some(_)) => "error",
+            (None, None) => "success",
+        }
+    }
+
+    fn check_key(&self) -> Option<&'static str> {
+        self.check.as_ref().map(|status| match status {
+            Status::Pass => "pass",
+            Status::Fail { .. } => "fail",
+            Status::Missing => "missing",
+        })
+    }
+
+    fn expected(&self) -> Option<&str> {
+        match &self.check {
+            Some(Status::Fail { expected }) => Some(expected),
+            _ => None,
+        }
+    }
+}
+
+pub fn records(reports: &[DayReport], parts: &[Part]) -> Vec<Record> {
+    let mut records = vec![];
+    for report in reports {
+        match &report.error {
+            Some(error) => records.extend(parts.iter().map(|part| Record {
+                day: report.day,
+                part: *part,
+                answer: None,
+                duration_ns: None,
+                error: Some(error.clone()),
+                failure: None,
+                check: None,
+            })),
+            None => records.extend(report.results.iter().map(|result| Record {
+                day: report.day,
+                part: result.part,
+                answer: result.answer.clone().ok(),
+                duration_ns: Some(result.duration.as_nanos()),
+                error: result.answer.as_ref().err().map(|failure| failure.to_string()),
+                failure: result.answer.clone().err(),
+                check: result.status.clone(),
+            })),
+        }
+    }
+
+    records
+}
+
+/// Writes the records as a JSON array or as CSV with a header line. Text is written by the runner as it goes
+pub fn write(out: &mut dyn Write, format: Format, records: &[Record]) -> io::Result<()> {
+    match format {
+        Format::Text => Ok(()),
+        Format::Json => write_json(out, records),
+        Format::Csv => write_csv(out, records),
+    }
+}
+
+fn write_json(out: &mut dyn Write, records: &[Record]) -> io::Result<()> {
+    writeln!(out, "[")?;
+    for (i, record) in records.iter().enumerate() {
+        let mut fields = vec![
+            format!("\"day\": {}", record.day),
+            format!("\"part\": {}", record.part),
+            format!("\"answer\": {}", json_option(record.answer.as_deref())),
+            format!(
+                "\"duration_ns\": {}",
+                record.duration_ns.map_or("null".to_string(), |nanos| nanos.to_string())
+            ),
+            format!("\"status\": {}", json_string(record.status())),
+            format!("\"error\": {}", json_option(record.error.as_deref())),
+        ];
+        if let Some(check) = record.check_key() {
+            fields.push(format!("\"check\": {}", json_string(check)));
+            fields.push(format!("\"expected\": {}", json_option(record.expected())));
+        }
+
+        let separator = if i + 1 < records.len() { "," } else { "" };
+        writeln!(out, "  {{{}}}{}", fields.join(", "), separator)?;
+    }
+    writeln!(out, "]")
+}
+
+fn json_option(value: Option<&str>) -> String {
+    value.map_or("null".to_string(), json_string)
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn write_csv(out: &mut dyn Write, records: &[Record]) -> io::Result<()> {
+    writeln!(out, "day,part,answer,duration_ns,status,error,check,expected")?;
+    for record in records {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            record.day,
+            record.part,
+            csv_field(record.answer.as_deref().unwrap_or("")),
+            record.duration_ns.map_or(String::new(), |nanos| nanos.to_string()),
+            record.status(),
+            csv_field(record.error.as_deref().unwrap_or("")),
+            record.check_key().unwrap_or(""),
+            csv_field(record.expected().unwrap_or("")),
+        )?;
+    }
+    Ok(())
+}
+
+/// Quotes a field when it contains a separator, a quote or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::runner::PartResult;
+
+    fn reports() -> Vec<DayReport> {
+        vec![
+            DayReport {
+                day: 20,
+                error: None,
+                results: vec![PartResult {
+                    part: Part::One,
+                    answer: Ok("5765".to_string()),
+                    duration: Duration::from_nanos(1500),
+                    cpu_time: None,
+                    status: Some(Status::Fail {
+                        expected: "5766".to_string(),
+                    }),
+                }],
+                wall_time: Duration::ZERO,
+            },
+            DayReport {
+                day: 21,
+                error: Some("Invalid input: day 21, line 1, column 1: \"oops\"".to_string()),
+                results: vec![],
+                wall_time: Duration::ZERO,
+            },
+        ]
+    }
+
+    fn written(format: Format) -> String {
+        let mut out = vec![];
+        write(&mut out, format, &records(&reports(), &[Part::One])).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_records() {
+        let records = records(&reports(), &Part::ALL);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].status(), "success");
+        assert_eq!(records[0].duration_ns, Some(1500));
+        assert_eq!((records[2].day, records[2].part, records[2].status()), (21, Part::Two, "error"));
+    }
+
+    #[test]
+    fn test_failure() {
+        let report = DayReport {
+            day: 19,
+            error: None,
+            results: vec![PartResult {
+                part: Part::Two,
+                answer: Err(Failure::Timeout(Duration::from_secs(5))),
+                duration: Duration::from_secs(5),
+                cpu_time: None,
+                status: None,
+            }],
+            wall_time: Duration::ZERO,
+        };
+        let records = records(&[report], &[Part::Two]);
+        assert_eq!(records[0].status(), "timeout");
+        assert_eq!(records[0].answer, None);
+        assert_eq!(records[0].error.as_deref(), Some("TIMEOUT (after 5s)"));
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            written(Format::Json),
+            "[\n  {\"day\": 20, \"part\": 1, \"answer\": \"5765\", \"duration_ns\": 1500, \"status\": \"success\", \"error\": null, \"check\": \"fail\", \"expected\": \"5766\"},\n  {\"day\": 21, \"part\": 1, \"answer\": null, \"duration_ns\": null, \"status\": \"error\", \"error\": \"Invalid input: day 21, line 1, column 1: \\\"oops\\\"\"}\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            written(Format::Csv),
+            "day,part,answer,duration_ns,status,error,check,expected\n20,1,5765,1500,success,,fail,5766\n21,1,,,error,\"Invalid input: day 21, line 1, column 1: \"\"oops\"\"\",,\n"
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("csv".parse(), Ok(Format::Csv));
+        assert!("yaml".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,614 @@
+use std::any::Any;
+use std::fmt::Display;
+use std::fs::read_to_string;
+use std::io::{self, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::answers::{self, Answers, Status};
+use crate::bench::{self, BenchHistory, Stats, Step};
+use crate::report::Format;
+use crate::{Day, Loader};
+
+pub const USAGE: &str = "Usage: advent <day> [--part 1|2] [--input <file>|-] [--check] [--answers <file>]\n                    [--format text|json|csv] [--timeout <seconds>] [--trace]\n                    [--bench <runs>] [--save <file>] [--baseline <file>] [--threshold <percent>]";
+pub const USAGE_ALL: &str = "Usage: all [--jobs <workers>] [--part 1|2] [--check] [--answers <file>]\n           [--format text|json|csv] [--timeout <seconds>]\n           [--bench <runs>] [--save <file>] [--baseline <file>] [--threshold <percent>]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub(crate) fn solve(&self, solution: &mut dyn Day) -> crate::Answer {
+        match self {
+            Part::One => solution.part1(),
+            Part::Two => solution.part2(),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Where to read the puzzle input from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Default,
+    Stdin,
+    File(PathBuf),
+}
+
+impl Input {
+    pub fn read(&self, day: u8) -> io::Result<String> {
+        match self {
+            Input::Default => read_to_string(default_input_path(day)),
+            Input::File(path) => read_to_string(path),
+            Input::Stdin => {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content)?;
+                Ok(content)
+            }
+        }
+    }
+}
+
+pub fn default_input_path(day: u8) -> PathBuf {
+    PathBuf::from(format!("inputs/day{}.txt", day))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub day: Option<u8>,
+    pub parts: Vec<Part>,
+    pub input: Input,
+    pub check: bool,
+    pub answers: PathBuf,
+    pub format: Format,
+    pub timeout: Option<Duration>,
+    pub trace: bool,
+    pub bench: Option<usize>,
+    pub save: Option<PathBuf>,
+    pub baseline: Option<PathBuf>,
+    pub threshold: f64,
+    pub jobs: Option<usize>,
+}
+
+impl Options {
+    /// Parses the command-line arguments, without the program name
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut day = None;
+        let mut parts = Part::ALL.to_vec();
+        let mut input = Input::Default;
+        let mut check = false;
+        let mut answers = PathBuf::from(answers::DEFAULT_PATH);
+        let mut format = Format::Text;
+        let mut timeout = None;
+        let mut trace = false;
+        let mut bench = None;
+        let mut save = None;
+        let mut baseline = None;
+        let mut threshold = bench::DEFAULT_THRESHOLD;
+        let mut jobs = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-p" | "--part" => {
+                    let value = args.next().ok_or("Missing value for --part")?;
+                    parts = match value.as_str() {
+                        "1" => vec![Part::One],
+                        "2" => vec![Part::Two],
+                        _ => return Err(format!("Invalid part: {}", value)),
+                    };
+                }
+                "-i" | "--input" => {
+                    let value = args.next().ok_or("Missing value for --input")?;
+                    input = match value.as_str() {
+                        "-" => Input::Stdin,
+                        _ => Input::File(PathBuf::from(value)),
+                    };
+                }
+                "-c" | "--check" => check = true,
+                "--answers" => {
+                    answers = PathBuf::from(args.next().ok_or("Missing value for --answers")?);
+                }
+                "-f" | "--format" => format = args.next().ok_or("Missing value for --format")?.parse()?,
+                "-t" | "--timeout" => {
+                    let value = args.next().ok_or("Missing value for --timeout")?;
+                    timeout = match value.parse().map(Duration::try_from_secs_f64) {
+                        Ok(Ok(duration)) if !duration.is_zero() => Some(duration),
+                        _ => return Err(format!("Invalid timeout: {}", value)),
+                    };
+                }
+                "--trace" => trace = true,
+                "-b" | "--bench" => {
+                    let value = args.next().ok_or("Missing value for --bench")?;
+                    bench = match value.parse() {
+                        Ok(runs) if runs > 0 => Some(runs),
+                        _ => return Err(format!("Invalid number of runs: {}", value)),
+                    };
+                }
+                "--save" => save = Some(PathBuf::from(args.next().ok_or("Missing value for --save")?)),
+                "--baseline" => baseline = Some(PathBuf::from(args.next().ok_or("Missing value for --baseline")?)),
+                "--threshold" => {
+                    let value = args.next().ok_or("Missing value for --threshold")?;
+                    threshold = value.parse().map_err(|_| format!("Invalid threshold: {}", value))?;
+                }
+                "-j" | "--jobs" => {
+                    let value = args.next().ok_or("Missing value for --jobs")?;
+                    jobs = match value.parse() {
+                        Ok(workers) if workers > 0 => Some(workers),
+                        _ => return Err(format!("Invalid number of workers: {}", value)),
+                    };
+                }
+                _ if day.is_none() && !arg.starts_with('-') => {
+                    day = Some(arg.parse().map_err(|_| format!("Invalid day: {}", arg))?);
+                }
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+
+        if bench.is_none() && (save.is_some() || baseline.is_some()) {
+            return Err("--save and --baseline only apply with --bench".to_string());
+        }
+        if bench.is_some() && format != Format::Text {
+            return Err("--format does not apply with --bench".to_string());
+        }
+        if bench.is_some() && timeout.is_some() {
+            return Err("--timeout does not apply with --bench".to_string());
+        }
+        if trace && (bench.is_some() || format != Format::Text) {
+            return Err("--trace only applies to the text output, without --bench".to_string());
+        }
+
+        Ok(Self {
+            day,
+            parts,
+            input,
+            check,
+            answers,
+            format,
+            timeout,
+            trace,
+            bench,
+            save,
+            baseline,
+            threshold,
+            jobs,
+        })
+    }
+
+    /// The expected answers, only loaded in check mode
+    pub fn load_answers(&self) -> Result<Option<Answers>, String> {
+        if !self.check {
+            return Ok(None);
+        }
+
+        Answers::load(&self.answers).map(Some)
+    }
+
+    /// The number of days to solve at the same time, one per core by default
+    pub fn workers(&self) -> usize {
+        self.jobs
+            .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1)
+    }
+
+    /// The results of an earlier benchmark to compare with, if any
+    pub fn load_baseline(&self) -> Result<Option<BenchHistory>, String> {
+        self.baseline.as_ref().map(BenchHistory::load).transpose()
+    }
+}
+
+/// Why a part gave no answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    Timeout(Duration),
+    Panic(String),
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Timeout(timeout) => write!(f, "TIMEOUT (after {:?})", timeout),
+            Failure::Panic(message) => write!(f, "PANIC ({})", message),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PartResult {
+    pub part: Part,
+    pub answer: Result<String, Failure>,
+    pub duration: Duration,
+    pub cpu_time: Option<Duration>,
+    pub status: Option<Status>,
+}
+
+impl PartResult {
+    pub fn failed(&self) -> bool {
+        self.answer.is_err() || matches!(self.status, Some(Status::Fail { .. }))
+    }
+}
+
+/// CPU time consumed by the current thread. Unlike the elapsed time,
+/// it doesn't grow when other threads compete for the same cores
+#[cfg(unix)]
+pub fn thread_cpu_time() -> Option<Duration> {
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: `time` is a valid timespec for clock_gettime to write into
+    let result = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) };
+    (result == 0).then(|| Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+#[cfg(not(unix))]
+pub fn thread_cpu_time() -> Option<Duration> {
+    None
+}
+
+/// Solves the requested parts of a day, writing each answer with its timing,
+/// and how it compares to the expected answer when some are given.
+/// A part that panics or times out loses the solution, which is then parsed again with `reload` for the next part
+pub fn solve(
+    out: &mut dyn Write,
+    day: u8,
+    solution: Box<dyn Day>,
+    reload: &dyn Fn() -> crate::Result<Box<dyn Day>>,
+    parts: &[Part],
+    answers: Option<&Answers>,
+    timeout: Option<Duration>,
+) -> io::Result<Vec<PartResult>> {
+    writeln!(out, "========= DAY {} ========", day)?;
+    let mut solution = Some(solution);
+    let mut results = vec![];
+    for part in parts {
+        write!(out, "Solving part {}: ", part)?;
+        out.flush()?;
+
+        let current = match solution.take() {
+            Some(current) => current,
+            None => reload().expect("The input was already parsed once"),
+        };
+        let start = Instant::now();
+        let (answer, duration, cpu_time) = match solve_isolated(current, *part, timeout) {
+            Ok((current, answer, duration, cpu_time)) => {
+                solution = Some(current);
+                (Ok(answer), duration, cpu_time)
+            }
+            Err(failure) => (Err(failure), start.elapsed(), None),
+        };
+
+        let status = match (&answer, answers) {
+            (Ok(answer), Some(answers)) => Some(answers.check(day, *part, answer)),
+            _ => None,
+        };
+        match (&answer, &status) {
+            (Err(failure), _) => writeln!(out, "{}", failure)?,
+            (Ok(answer), Some(status)) => writeln!(out, "{} (took {:?}) {}", answer, duration, status)?,
+            (Ok(answer), None) => writeln!(out, "{} (took {:?})", answer, duration)?,
+        }
+
+        results.push(PartResult {
+            part: *part,
+            answer,
+            duration,
+            cpu_time,
+            status,
+        });
+    }
+    writeln!(out)?;
+
+    Ok(results)
+}
+
+/// Solves a part on its own thread, so that a panic only takes down that thread and a part running
+/// past the timeout can be left behind. The solution comes back with the answer, its duration and CPU time
+#[allow(clippy::type_complexity)]
+fn solve_isolated(
+    mut solution: Box<dyn Day>,
+    part: Part,
+    timeout: Option<Duration>,
+) -> Result<(Box<dyn Day>, String, Duration, Option<Duration>), Failure> {
+    let (sender, receiver) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        let start = Instant::now();
+        let cpu_start = thread_cpu_time();
+        let answer = part.solve(solution.as_mut()).to_string();
+        let duration = start.elapsed();
+        let cpu_time = cpu_start.zip(thread_cpu_time()).map(|(start, end)| end - start);
+        // Nobody is listening anymore after a timeout
+        let _ = sender.send((solution, answer, duration, cpu_time));
+    });
+
+    let received = match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout),
+        None => receiver.recv().map_err(RecvTimeoutError::from),
+    };
+    match received {
+        Ok(result) => Ok(result),
+        Err(RecvTimeoutError::Timeout) => Err(Failure::Timeout(timeout.unwrap_or_default())),
+        Err(RecvTimeoutError::Disconnected) => match handle.join() {
+            Err(payload) => Err(Failure::Panic(panic_message(payload))),
+            Ok(()) => unreachable!("The thread only returns after sending its answer"),
+        },
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+/// Everything `run_day` did for a day, kept aside so that days solved concurrently can be reported in order
+#[derive(Debug)]
+pub struct DayReport {
+    pub day: u8,
+    pub error: Option<String>,
+    pub results: Vec<PartResult>,
+    pub wall_time: Duration,
+}
+
+impl DayReport {
+    pub fn failed(&self) -> bool {
+        self.error.is_some() || self.results.iter().any(|result| result.failed())
+    }
+}
+
+/// Reads the input of a day, parses it and solves the requested parts, writing the progress to `out`.
+/// Panics are caught, so that a broken day is reported like any other
+pub fn run_day(
+    out: &mut dyn Write,
+    day: u8,
+    loader: Loader,
+    input: &Input,
+    parts: &[Part],
+    answers: Option<&Answers>,
+    timeout: Option<Duration>,
+) -> io::Result<DayReport> {
+    let start = Instant::now();
+    let mut results = vec![];
+
+    let error = match input.read(day) {
+        Err(err) => Some(format!("Could not read the input of day {}: {}", day, err)),
+        Ok(input) => match panic::catch_unwind(AssertUnwindSafe(|| loader(&input))) {
+            Err(payload) => Some(format!("Panic while parsing: {}", panic_message(payload))),
+            Ok(Err(err)) => Some(format!("Invalid input: {}", err)),
+            Ok(Ok(solution)) => {
+                results = solve(out, day, solution, &|| loader(&input), parts, answers, timeout)?;
+                None
+            }
+        },
+    };
+
+    Ok(DayReport {
+        day,
+        error,
+        results,
+        wall_time: start.elapsed(),
+    })
+}
+
+/// A table with the wall time of each day and the CPU time of each of its parts
+pub fn summary(reports: &[DayReport], total: Duration) -> String {
+    let format_time = |time: Option<Duration>| match time {
+        Some(time) => format!("{:.3?}", time),
+        None => "-".to_string(),
+    };
+
+    let mut table = format!("{:>5} | {:>12} | {:>12} | {:>12}\n", "Day", "Wall", "Part 1 CPU", "Part 2 CPU");
+    table.push_str(&format!("{:-<6}+{:-<14}+{:-<14}+{:-<13}\n", "", "", "", ""));
+    for report in reports {
+        let cpu_time = |part: Part| {
+            report
+                .results
+                .iter()
+                .find(|result| result.part == part)
+                .and_then(|result| result.cpu_time)
+        };
+        table.push_str(&format!(
+            "{:>5} | {:>12} | {:>12} | {:>12}\n",
+            report.day,
+            format_time(Some(report.wall_time)),
+            format_time(cpu_time(Part::One)),
+            format_time(cpu_time(Part::Two)),
+        ));
+    }
+    table.push_str(&format!("{:>5} | {:>12} |\n", "Total", format_time(Some(total))));
+
+    table
+}
+
+/// Benchmarks a day, writing the statistics of each step and flagging the ones slower than the baseline.
+/// An input that can't be parsed is returned as the inner error
+pub fn bench(
+    out: &mut dyn Write,
+    day: u8,
+    loader: Loader,
+    input: &str,
+    options: &Options,
+    baseline: Option<&BenchHistory>,
+) -> io::Result<crate::Result<Vec<(Step, Stats)>>> {
+    let runs = options.bench.unwrap_or(1);
+    writeln!(out, "========= DAY {} ========", day)?;
+    let stats = match bench::bench(loader, input, &options.parts, runs) {
+        Ok(stats) => stats,
+        Err(err) => return Ok(Err(err)),
+    };
+
+    for (step, step_stats) in &stats {
+        let regression = baseline
+            .and_then(|baseline| baseline.get(day, *step))
+            .and_then(|previous| bench::regression(step_stats, previous, options.threshold));
+        match regression {
+            Some(change) => writeln!(
+                out,
+                "{}: {} ({} runs) REGRESSION ({:+.1}% median)",
+                step, step_stats, runs, change
+            )?,
+            None => writeln!(out, "{}: {} ({} runs)", step, step_stats, runs)?,
+        }
+    }
+    writeln!(out)?;
+
+    Ok(Ok(stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_day_only() {
+        let options = Options::from_args(args("19")).unwrap();
+        assert_eq!(options.day, Some(19));
+        assert_eq!(options.parts, vec![Part::One, Part::Two]);
+        assert_eq!(options.input, Input::Default);
+    }
+
+    #[test]
+    fn test_part_and_input() {
+        let options = Options::from_args(args("19 --part 2 --input path/to/file")).unwrap();
+        assert_eq!(options.parts, vec![Part::Two]);
+        assert_eq!(options.input, Input::File(PathBuf::from("path/to/file")));
+    }
+
+    #[test]
+    fn test_stdin() {
+        let options = Options::from_args(args("-i - 18")).unwrap();
+        assert_eq!(options.day, Some(18));
+        assert_eq!(options.input, Input::Stdin);
+    }
+
+    #[test]
+    fn test_check() {
+        let options = Options::from_args(args("20 --check")).unwrap();
+        assert!(options.check);
+        assert_eq!(options.answers, PathBuf::from("answers.toml"));
+
+        let options = Options::from_args(args("--check --answers other.toml")).unwrap();
+        assert_eq!(options.day, None);
+        assert_eq!(options.answers, PathBuf::from("other.toml"));
+    }
+
+    #[test]
+    fn test_bench() {
+        let options = Options::from_args(args("18 --bench 20 --baseline old.toml --save new.toml")).unwrap();
+        assert_eq!(options.bench, Some(20));
+        assert_eq!(options.baseline, Some(PathBuf::from("old.toml")));
+        assert_eq!(options.save, Some(PathBuf::from("new.toml")));
+        assert_eq!(options.threshold, 10.);
+
+        assert!(Options::from_args(args("18 --bench 0")).is_err());
+        assert!(Options::from_args(args("18 --save new.toml")).is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Options::from_args(args("19")).unwrap().format, Format::Text);
+        assert_eq!(Options::from_args(args("19 --format json")).unwrap().format, Format::Json);
+        assert!(Options::from_args(args("19 --format yaml")).is_err());
+        assert!(Options::from_args(args("19 --format csv --bench 5")).is_err());
+    }
+
+    #[test]
+    fn test_trace() {
+        assert!(Options::from_args(args("18 --trace")).unwrap().trace);
+        assert!(!Options::from_args(args("18")).unwrap().trace);
+        assert!(Options::from_args(args("18 --trace --format json")).is_err());
+    }
+
+    #[test]
+    fn test_jobs() {
+        assert_eq!(Options::from_args(args("-j 3")).unwrap().workers(), 3);
+        assert!(Options::from_args(args("")).unwrap().workers() >= 1);
+        assert!(Options::from_args(args("--jobs 0")).is_err());
+    }
+
+    struct Faulty;
+
+    impl Day for Faulty {
+        fn parse(_input: &str) -> crate::Result<Self> {
+            Ok(Faulty)
+        }
+
+        fn part1(&mut self) -> crate::Answer {
+            panic!("broken part");
+        }
+
+        fn part2(&mut self) -> crate::Answer {
+            thread::sleep(Duration::from_secs(5));
+            Box::new(0)
+        }
+    }
+
+    #[test]
+    fn test_bench_output() {
+        let options = Options::from_args(args("18 --bench 2 --part 1")).unwrap();
+        let input = include_str!("../inputs/examples/day18.txt");
+        let loader = crate::loader(18).unwrap();
+
+        let mut out = vec![];
+        let stats = bench(&mut out, 18, loader, input, &options, None).unwrap().unwrap();
+        assert_eq!(stats.iter().map(|(step, _)| *step).collect::<Vec<_>>(), [Step::Parse, Step::Solve(Part::One)]);
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.starts_with("========= DAY 18 ========\nParsing: "));
+        assert!(output.contains(" (2 runs)\nPart 1: "));
+
+        let mut out = vec![];
+        assert!(bench(&mut out, 18, loader, "[1,", &options, None).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_isolation() {
+        let mut out = vec![];
+        let reload = || Ok(Box::new(Faulty) as Box<dyn Day>);
+        // Long enough for the panic to be reported even when capturing a backtrace
+        let timeout = Some(Duration::from_secs(1));
+        let results = solve(&mut out, 99, Box::new(Faulty), &reload, &Part::ALL, None, timeout).unwrap();
+        assert_eq!(results[0].answer, Err(Failure::Panic("broken part".to_string())));
+        assert_eq!(results[1].answer, Err(Failure::Timeout(Duration::from_secs(1))));
+        assert!(results.iter().all(|result| result.failed()));
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("Solving part 1: PANIC (broken part)\n"));
+        assert!(output.contains("Solving part 2: TIMEOUT (after 1s)\n"));
+    }
+
+    #[test]
+    fn test_timeout() {
+        assert_eq!(Options::from_args(args("19 -t 1.5")).unwrap().timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(Options::from_args(args("19")).unwrap().timeout, None);
+        assert!(Options::from_args(args("19 --timeout 0")).is_err());
+        assert!(Options::from_args(args("19 --timeout inf")).is_err());
+        assert!(Options::from_args(args("19 --timeout 1e30")).is_err());
+        assert!(Options::from_args(args("19 --timeout 5 --bench 3")).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Options::from_args(args("19 --part 3")).is_err());
+        assert!(Options::from_args(args("19 --input")).is_err());
+        assert!(Options::from_args(args("19 20")).is_err());
+    }
+}
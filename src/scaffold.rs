@@ -0,0 +1,173 @@
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+
+/// The skeleton of a new day, with `REPLACEDAY` standing for the day number
+const TEMPLATE: &str = include_str!("template.rs");
+const PLACEHOLDER: &str = "REPLACEDAY";
+
+const REGISTRY_START: &str = "pub const REGISTRY: &[(u8, Loader)] = &[";
+const REGISTRY_END: &str = "];";
+
+pub fn source(day: u8) -> String {
+    TEMPLATE.replace(PLACEHOLDER, &day.to_string())
+}
+
+/// Creates the files of a new day under `root` and registers it in `src/lib.rs`.
+/// Files that already exist are left untouched, so running it twice is harmless.
+/// Returns a line describing each step, for the caller to print
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<String>, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("Invalid day: {}", day));
+    }
+
+    let mut steps = vec![];
+    for (path, content) in [
+        (format!("src/day{}.rs", day), source(day)),
+        (format!("inputs/day{}.txt", day), String::new()),
+        (format!("inputs/examples/day{}.txt", day), String::new()),
+    ] {
+        let full_path = root.join(&path);
+        if full_path.exists() {
+            steps.push(format!("Kept {}, it already exists", path));
+            continue;
+        }
+
+        if let Some(parent) = full_path.parent() {
+            create_dir_all(parent).map_err(|err| format!("Could not create {}: {}", parent.display(), err))?;
+        }
+        write(&full_path, content).map_err(|err| format!("Could not write {}: {}", path, err))?;
+        steps.push(format!("Created {}", path));
+    }
+
+    let lib_path = root.join("src/lib.rs");
+    let lib = read_to_string(&lib_path).map_err(|err| format!("Could not read src/lib.rs: {}", err))?;
+    let registered = register(&lib, day)?;
+    if registered == lib {
+        steps.push(format!("Kept src/lib.rs, day {} is already registered", day));
+    } else {
+        write(&lib_path, registered).map_err(|err| format!("Could not write src/lib.rs: {}", err))?;
+        steps.push(format!("Registered day {} in src/lib.rs", day));
+    }
+
+    Ok(steps)
+}
+
+/// Adds the `pub mod` line and the registry entry of a day to the source of `lib.rs`,
+/// in day order, unless they are already there
+pub fn register(lib: &str, day: u8) -> Result<String, String> {
+    let mut lines = lib.lines().map(String::from).collect::<Vec<_>>();
+
+    let modules = 0..lines.len();
+    insert_sorted(&mut lines, modules, day, module_day, format!("pub mod day{};", day))
+        .ok_or("src/lib.rs has no `pub mod dayN;` line to add the module after")?;
+
+    let start = lines
+        .iter()
+        .position(|line| line.trim() == REGISTRY_START)
+        .ok_or("src/lib.rs has no REGISTRY")?;
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.trim() == REGISTRY_END)
+        .map(|offset| start + offset)
+        .ok_or("the REGISTRY of src/lib.rs is not closed")?;
+    let entry = format!("    ({}, load::<day{}::Solution>),", day, day);
+    // An empty registry takes the entry right before its closing bracket
+    if insert_sorted(&mut lines, start + 1..end, day, entry_day, entry.clone()).is_none() {
+        lines.insert(end, entry);
+    }
+
+    let mut registered = lines.join("\n");
+    if lib.ends_with('\n') {
+        registered.push('\n');
+    }
+    Ok(registered)
+}
+
+/// Inserts `line` among the lines of `range` holding a day, before the first later day.
+/// Returns None when none of these lines holds a day
+fn insert_sorted(
+    lines: &mut Vec<String>,
+    range: std::ops::Range<usize>,
+    day: u8,
+    parse_day: fn(&str) -> Option<u8>,
+    line: String,
+) -> Option<()> {
+    let days = range
+        .filter_map(|i| parse_day(&lines[i]).map(|existing| (i, existing)))
+        .collect::<Vec<_>>();
+    if days.iter().any(|(_, existing)| *existing == day) {
+        return Some(());
+    }
+
+    let (last, _) = days.last()?;
+    let index = match days.iter().find(|(_, existing)| *existing > day) {
+        Some((i, _)) => *i,
+        None => last + 1,
+    };
+    lines.insert(index, line);
+    Some(())
+}
+
+/// The day of a `pub mod dayN;` line
+fn module_day(line: &str) -> Option<u8> {
+    line.strip_prefix("pub mod day")?.strip_suffix(';')?.parse().ok()
+}
+
+/// The day of a `(N, load::<dayN::Solution>),` registry entry
+fn entry_day(line: &str) -> Option<u8> {
+    let (day, _) = line.trim().strip_prefix('(')?.split_once(',')?;
+    day.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "pub mod day18;
+pub mod day20;
+
+pub mod runner;
+
+pub const REGISTRY: &[(u8, Loader)] = &[
+    (18, load::<day18::Solution>),
+    (20, load::<day20::Solution>),
+];
+";
+
+    #[test]
+    fn test_register() {
+        let lib = register(LIB, 19).unwrap();
+        assert!(lib.contains("pub mod day18;\npub mod day19;\npub mod day20;\n"));
+        assert!(lib.contains(
+            "    (18, load::<day18::Solution>),\n    (19, load::<day19::Solution>),\n    (20, load::<day20::Solution>),\n];\n"
+        ));
+
+        let lib = register(LIB, 25).unwrap();
+        assert!(lib.contains("pub mod day20;\npub mod day25;\n\npub mod runner;"));
+        assert!(lib.contains("    (25, load::<day25::Solution>),\n];"));
+
+        let lib = register(LIB, 1).unwrap();
+        assert!(lib.starts_with("pub mod day1;\npub mod day18;"));
+    }
+
+    #[test]
+    fn test_register_twice() {
+        let lib = register(LIB, 19).unwrap();
+        assert_eq!(register(&lib, 19).unwrap(), lib);
+        assert_eq!(register(LIB, 18).unwrap(), LIB);
+    }
+
+    #[test]
+    fn test_register_invalid() {
+        assert!(register("pub mod runner;\n", 19).is_err());
+        assert!(register("pub mod day18;\n", 19).is_err());
+    }
+
+    #[test]
+    fn test_source() {
+        let source = source(7);
+        assert!(source.contains("const DAY: u8 = 7;"));
+        assert!(source.contains("\"../inputs/examples/day7.txt\""));
+        assert!(!source.contains(PLACEHOLDER));
+    }
+}
@@ -1,49 +1,46 @@
-use std::fs::read_to_string;
-use std::io::{self, Write};
-
 #[allow(unused_imports)]
 use itertools::Itertools;
 
+use crate::{Answer, Day, Result};
+
+const DAY: u8 = REPLACEDAY;
+
 pub struct Solution {
     lines: Vec<String>,
 }
 
-impl Solution {
-    pub fn init() -> Self {
-        let mut lines = Vec::new();
-        for line in read_to_string("inputs/dayREPLACEDAY.txt").unwrap().lines() {
-            lines.push(line.to_string());
-        }
+impl Day for Solution {
+    fn parse(input: &str) -> Result<Self> {
+        let lines = input.lines().map(String::from).collect();
 
-        Self {
-            lines,
-        }
+        Ok(Self { lines })
     }
 
-    fn part1(&mut self) {
+    fn part1(&mut self) -> Answer {
+        Box::new("")
+    }
 
+    fn part2(&mut self) -> Answer {
+        Box::new("")
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn part2(&mut self) {
+    // Paste the example of the puzzle statement in this file, and its answers below
+    const EXAMPLE: &str = include_str!("../inputs/examples/dayREPLACEDAY.txt");
 
+    #[test]
+    fn test_example_part1() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part1().to_string(), "");
     }
 
-    pub fn solve(&mut self) {
-        println!("========= DAY REPLACEDAY ========");
-        print!("Solving part 1: ");
-        io::stdout().flush().unwrap();
-
-        let start = std::time::Instant::now();
-        let part1 = self.part1();
-        let part1_time = start.elapsed();
-        println!("{:?} (took {:?})", part1, part1_time);
-
-        print!("Solving part 2: ");
-        io::stdout().flush().unwrap();
-        let start = std::time::Instant::now();
-        let part2 = self.part2();
-        let part2_time = start.elapsed();
-        println!("{:?} (took {:?})", part2, part2_time);
-        println!();
+    #[test]
+    fn test_example_part2() {
+        let mut solution = Solution::parse(EXAMPLE).unwrap();
+        assert_eq!(solution.part2().to_string(), "");
     }
-}
\ No newline at end of file
+}